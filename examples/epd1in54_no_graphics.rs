@@ -1,7 +1,10 @@
 #![deny(warnings)]
 
 use embedded_hal::prelude::*;
-use epd_waveshare::{epd1in54::Epd1in54, prelude::*};
+use epd_waveshare::{
+    epd1in54::{self, Epd1in54},
+    prelude::*,
+};
 use linux_embedded_hal::{
     spidev::{self, SpidevOptions},
     sysfs_gpio::Direction,
@@ -18,7 +21,7 @@ fn main() -> Result<(), std::io::Error> {
     let mut spi = Spidev::open("/dev/spidev0.0")?;
     let options = SpidevOptions::new()
         .bits_per_word(8)
-        .max_speed_hz(4_000_000)
+        .max_speed_hz(epd1in54::SPI_MAX_SPEED_HZ)
         .mode(spidev::SpiModeFlags::SPI_MODE_0)
         .build();
     spi.configure(&options).expect("spi configuration");
@@ -9,7 +9,7 @@ use embedded_graphics::{
 use embedded_hal::prelude::*;
 use epd_waveshare::{
     color::*,
-    epd2in13_v2::{Display2in13, Epd2in13},
+    epd2in13_v2::{self, Display2in13, Epd2in13},
     graphics::DisplayRotation,
     prelude::*,
 };
@@ -30,7 +30,7 @@ fn main() -> Result<(), std::io::Error> {
     let mut spi = Spidev::open("/dev/spidev0.0").expect("spidev directory");
     let options = SpidevOptions::new()
         .bits_per_word(8)
-        .max_speed_hz(4_000_000)
+        .max_speed_hz(epd2in13_v2::SPI_MAX_SPEED_HZ)
         .mode(spidev::SpiModeFlags::SPI_MODE_0)
         .build();
     spi.configure(&options).expect("spi configuration");
@@ -9,7 +9,7 @@ use embedded_graphics::{
 use embedded_hal::prelude::*;
 use epd_waveshare::{
     color::*,
-    epd2in13bc::{Display2in13bc, Epd2in13bc},
+    epd2in13bc::{self, Display2in13bc, Epd2in13bc},
     graphics::DisplayRotation,
     prelude::*,
 };
@@ -64,7 +64,7 @@ fn main() -> Result<(), std::io::Error> {
     let mut spi = Spidev::open("/dev/spidev0.0").expect("spidev directory");
     let options = SpidevOptions::new()
         .bits_per_word(8)
-        .max_speed_hz(10_000_000)
+        .max_speed_hz(epd2in13bc::SPI_MAX_SPEED_HZ)
         .mode(spidev::SpiModeFlags::SPI_MODE_0)
         .build();
     spi.configure(&options).expect("spi configuration");
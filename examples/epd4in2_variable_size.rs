@@ -67,7 +67,8 @@ fn main() -> Result<(), std::io::Error> {
 
     println!("Test all the rotations");
 
-    let (x, y, width, height) = (50, 50, 250, 250);
+    // x and width are multiples of 8, so the buffer matches the partial window
+    let (x, y, width, height) = (48, 50, 256, 250);
 
     let mut buffer = [epd4in2::DEFAULT_BACKGROUND_COLOR.get_byte_value(); 62500]; //250*250
     let mut display = VarDisplay::new(width, height, &mut buffer, false).unwrap();
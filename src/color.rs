@@ -65,6 +65,22 @@ pub enum OctColor {
     HiZ = 0x07,
 }
 
+/// For displays supporting 4 levels of gray
+///
+/// Pixels use 2 bits in a single buffer, the leftmost pixel of a byte being stored in the
+/// two most significant bits. Like for [Color], set bits mean white.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gray4 {
+    /// Black color
+    Black = 0b00,
+    /// Dark gray color
+    DarkGray = 0b01,
+    /// Light gray color
+    LightGray = 0b10,
+    /// White color
+    White = 0b11,
+}
+
 /// Color trait for use in `Display`s
 pub trait ColorType: PixelColor {
     /// Number of bit used to represent this color type in a single buffer.
@@ -130,6 +146,15 @@ impl ColorType for OctColor {
     }
 }
 
+impl ColorType for Gray4 {
+    const BITS_PER_PIXEL_PER_BUFFER: usize = 2;
+    const BUFFER_COUNT: usize = 1;
+    fn bitmask(&self, _bwrbit: bool, pos: u32) -> (u8, u16) {
+        let shift = 6 - 2 * (pos % 4);
+        (!(0b11 << shift), (*self as u16) << shift)
+    }
+}
+
 #[cfg(feature = "graphics")]
 impl From<BinaryColor> for OctColor {
     fn from(b: BinaryColor) -> OctColor {
@@ -392,6 +417,101 @@ impl From<TriColor> for embedded_graphics_core::pixelcolor::Rgb888 {
     }
 }
 
+impl Gray4 {
+    /// Get the 2 bit encoding of the color
+    pub fn get_bit_value(self) -> u8 {
+        self as u8
+    }
+
+    /// Gets a full byte of pixels of this color
+    pub fn get_byte_value(self) -> u8 {
+        self as u8 * 0b0101_0101
+    }
+
+    /// Gets the color from its 2 bit encoding, higher bits are ignored
+    pub fn from_bits(bits: u8) -> Gray4 {
+        match bits & 0b11 {
+            0b00 => Gray4::Black,
+            0b01 => Gray4::DarkGray,
+            0b10 => Gray4::LightGray,
+            _ => Gray4::White,
+        }
+    }
+
+    /// Returns the next darker color, Black stays Black
+    pub fn darker(self) -> Gray4 {
+        Gray4::from_bits((self as u8).saturating_sub(1))
+    }
+
+    /// Returns the inverse of the given color.
+    ///
+    /// Black returns White and DarkGray returns LightGray
+    pub fn inverse(self) -> Gray4 {
+        match self {
+            Gray4::Black => Gray4::White,
+            Gray4::DarkGray => Gray4::LightGray,
+            Gray4::LightGray => Gray4::DarkGray,
+            Gray4::White => Gray4::Black,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelColor for Gray4 {
+    type Raw = embedded_graphics_core::pixelcolor::raw::RawU2;
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::raw::RawU2> for Gray4 {
+    fn from(b: embedded_graphics_core::pixelcolor::raw::RawU2) -> Self {
+        use embedded_graphics_core::prelude::RawData;
+        Gray4::from_bits(b.into_inner())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for Gray4 {
+    fn from(b: BinaryColor) -> Gray4 {
+        match b {
+            BinaryColor::On => Gray4::Black,
+            BinaryColor::Off => Gray4::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Color> for Gray4 {
+    fn from(color: Color) -> Gray4 {
+        match color {
+            Color::Black => Gray4::Black,
+            Color::White => Gray4::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Rgb888> for Gray4 {
+    fn from(rgb: embedded_graphics_core::pixelcolor::Rgb888) -> Self {
+        use embedded_graphics_core::pixelcolor::RgbColor;
+        // choose the closest gray level of the average brightness
+        let brightness = (rgb.r() as u16 + rgb.g() as u16 + rgb.b() as u16) / 3;
+        match brightness {
+            0..=42 => Gray4::Black,
+            43..=127 => Gray4::DarkGray,
+            128..=212 => Gray4::LightGray,
+            _ => Gray4::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Gray4> for embedded_graphics_core::pixelcolor::Rgb888 {
+    fn from(gray: Gray4) -> Self {
+        let value = gray as u8 * 0x55;
+        Self::new(value, value, value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -412,6 +532,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn gray4_bitmask() {
+        assert_eq!(Gray4::White.bitmask(false, 0), (0b0011_1111, 0b1100_0000));
+        assert_eq!(
+            Gray4::LightGray.bitmask(false, 1),
+            (0b1100_1111, 0b0010_0000)
+        );
+        assert_eq!(
+            Gray4::DarkGray.bitmask(false, 6),
+            (0b1111_0011, 0b0000_0100)
+        );
+        assert_eq!(Gray4::Black.bitmask(false, 3), (0b1111_1100, 0b0000_0000));
+    }
+
+    #[test]
+    fn gray4_byte_value() {
+        assert_eq!(Gray4::White.get_byte_value(), 0xFF);
+        assert_eq!(Gray4::LightGray.get_byte_value(), 0xAA);
+        assert_eq!(Gray4::DarkGray.get_byte_value(), 0x55);
+        assert_eq!(Gray4::Black.get_byte_value(), 0x00);
+    }
+
     #[test]
     fn u8_conversion_black() {
         assert_eq!(Color::from(Color::Black.get_bit_value()), Color::Black);
@@ -50,6 +50,10 @@ pub const WIDTH: u32 = 200;
 pub const HEIGHT: u32 = 200;
 /// Default Background Color
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// SPI clock speed used by the example of this display, higher speeds might be possible
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 //const DPI: u16 = 184;
 const IS_BUSY_LOW: bool = false;
 
@@ -65,7 +69,7 @@ use crate::type_a::{
 
 use crate::color::Color;
 
-use crate::traits::{RefreshLut, WaveshareDisplay};
+use crate::traits::{RefreshLut, SupportsPartial, WaveshareDisplay};
 
 use crate::buffer_len;
 use crate::interface::DisplayInterface;
@@ -145,6 +149,8 @@ where
     }
 }
 
+impl<SPI, CS, BUSY, DC, RST, DELAY> SupportsPartial for Epd1in54<SPI, CS, BUSY, DC, RST, DELAY> {}
+
 impl<SPI, CS, BUSY, DC, RST, E, DELAY> WaveshareDisplay<SPI, CS, BUSY, DC, RST, DELAY>
     for Epd1in54<SPI, CS, BUSY, DC, RST, DELAY>
 where
@@ -205,6 +211,7 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.wait_until_idle(spi, delay)?;
         self.use_full_frame(spi, delay)?;
         self.interface
@@ -223,12 +230,13 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(width as usize, height as usize));
         self.wait_until_idle(spi, delay)?;
         self.set_ram_area(spi, delay, x, y, x + width, y + height)?;
         self.set_ram_counter(spi, delay, x, y)?;
 
         self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
+            .cmd_with_rows(spi, Command::WriteRam, buffer, width)?;
         Ok(())
     }
 
@@ -8,6 +8,10 @@ pub const WIDTH: u32 = 200;
 pub const HEIGHT: u32 = 200;
 /// Default Background Color
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Conservative default SPI clock speed, not tested with this display
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 const IS_BUSY_LOW: bool = false;
 
 use embedded_hal::{
@@ -22,8 +26,9 @@ use crate::epd1in54_v2::constants::{LUT_FULL_UPDATE, LUT_PARTIAL_UPDATE};
 
 use crate::color::Color;
 
-use crate::traits::{RefreshLut, WaveshareDisplay};
+use crate::traits::{RefreshLut, SupportsPartial, WaveshareDisplay};
 
+use crate::buffer_len;
 use crate::interface::DisplayInterface;
 
 #[cfg(feature = "graphics")]
@@ -90,6 +95,8 @@ where
     }
 }
 
+impl<SPI, CS, BUSY, DC, RST, DELAY> SupportsPartial for Epd1in54<SPI, CS, BUSY, DC, RST, DELAY> {}
+
 impl<SPI, CS, BUSY, DC, RST, E, DELAY> WaveshareDisplay<SPI, CS, BUSY, DC, RST, DELAY>
     for Epd1in54<SPI, CS, BUSY, DC, RST, DELAY>
 where
@@ -148,6 +155,7 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.wait_until_idle(spi, delay)?;
         self.use_full_frame(spi, delay)?;
         self.interface
@@ -166,12 +174,13 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(width as usize, height as usize));
         self.wait_until_idle(spi, delay)?;
         self.set_ram_area(spi, delay, x, y, x + width, y + height)?;
         self.set_ram_counter(spi, delay, x, y)?;
 
         self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
+            .cmd_with_rows(spi, Command::WriteRam, buffer, width)?;
         Ok(())
     }
 
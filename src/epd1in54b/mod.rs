@@ -20,6 +20,10 @@ pub const WIDTH: u32 = 200;
 pub const HEIGHT: u32 = 200;
 /// Default Background Color (white)
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Conservative default SPI clock speed, not tested with this display
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 const IS_BUSY_LOW: bool = true;
 
 use crate::color::Color;
@@ -219,6 +223,7 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.wait_until_idle(spi, delay)?;
         self.send_resolution(spi)?;
 
@@ -16,6 +16,10 @@ pub const WIDTH: u32 = 152;
 pub const HEIGHT: u32 = 152;
 /// Default Background Color (white)
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Conservative default SPI clock speed, not tested with this display
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 const IS_BUSY_LOW: bool = true;
 const NUM_DISPLAY_BITS: u32 = WIDTH * HEIGHT / 8;
 
@@ -191,6 +195,7 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.update_achromatic_frame(spi, delay, buffer)?;
 
         // Clear the chromatic layer
@@ -23,7 +23,7 @@ use embedded_hal::{
 use crate::buffer_len;
 use crate::color::Color;
 use crate::interface::DisplayInterface;
-use crate::traits::{InternalWiAdditions, RefreshLut, WaveshareDisplay};
+use crate::traits::{InternalWiAdditions, RefreshLut, SupportsPartial, WaveshareDisplay};
 
 pub(crate) mod command;
 use self::command::{
@@ -62,6 +62,10 @@ pub const HEIGHT: u32 = 250;
 
 /// Default Background Color
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// SPI clock speed used by the example of this display, higher speeds might be possible
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 const IS_BUSY_LOW: bool = false;
 
 /// Epd2in13 (V2 & V3) driver
@@ -141,7 +145,7 @@ where
 
             // Use simple X/Y auto increase
             self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
-            self.set_ram_address_counters(spi, delay, 0, 0)?;
+            self.set_ram_counter(spi, delay, 0, 0)?;
 
             self.set_border_waveform(
                 spi,
@@ -172,6 +176,8 @@ where
     }
 }
 
+impl<SPI, CS, BUSY, DC, RST, DELAY> SupportsPartial for Epd2in13<SPI, CS, BUSY, DC, RST, DELAY> {}
+
 impl<SPI, CS, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, CS, BUSY, DC, RST, DELAY>
     for Epd2in13<SPI, CS, BUSY, DC, RST, DELAY>
 where
@@ -233,16 +239,18 @@ where
     ) -> Result<(), SPI::Error> {
         assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
-        self.set_ram_address_counters(spi, delay, 0, 0)?;
+        self.set_ram_counter(spi, delay, 0, 0)?;
 
-        self.cmd_with_data(spi, Command::WriteRam, buffer)?;
+        self.interface
+            .cmd_with_rows(spi, Command::WriteRam, buffer, WIDTH)?;
 
         if self.refresh == RefreshLut::Full {
             // Always keep the base buffer equal to current if not doing partial refresh.
             self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
-            self.set_ram_address_counters(spi, delay, 0, 0)?;
+            self.set_ram_counter(spi, delay, 0, 0)?;
 
-            self.cmd_with_data(spi, Command::WriteRamRed, buffer)?;
+            self.interface
+                .cmd_with_rows(spi, Command::WriteRamRed, buffer, WIDTH)?;
         }
         Ok(())
     }
@@ -260,7 +268,7 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        assert!((width * height / 8) as usize == buffer.len());
+        assert!(buffer_len(width as usize, height as usize) == buffer.len());
 
         // This should not be used when doing partial refresh. The RAM_RED must
         // be updated with the last buffer having been displayed. Doing partial
@@ -269,18 +277,23 @@ where
         // display incorrect as the controler will compare with something
         // incorrect.
         assert!(self.refresh == RefreshLut::Full);
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
 
-        self.set_ram_area(spi, x, y, x + width, y + height)?;
-        self.set_ram_address_counters(spi, delay, x, y)?;
+        self.set_ram_area(spi, x, y, x + width - 1, y + height - 1)?;
+        self.set_ram_counter(spi, delay, x, y)?;
 
-        self.cmd_with_data(spi, Command::WriteRam, buffer)?;
+        self.interface
+            .cmd_with_rows(spi, Command::WriteRam, buffer, width)?;
 
         if self.refresh == RefreshLut::Full {
             // Always keep the base buffer equals to current if not doing partial refresh.
-            self.set_ram_area(spi, x, y, x + width, y + height)?;
-            self.set_ram_address_counters(spi, delay, x, y)?;
+            self.set_ram_area(spi, x, y, x + width - 1, y + height - 1)?;
+            self.set_ram_counter(spi, delay, x, y)?;
 
-            self.cmd_with_data(spi, Command::WriteRamRed, buffer)?;
+            self.interface
+                .cmd_with_rows(spi, Command::WriteRamRed, buffer, width)?;
         }
 
         Ok(())
@@ -327,7 +340,7 @@ where
         let color = self.background_color.get_byte_value();
 
         self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
-        self.set_ram_address_counters(spi, delay, 0, 0)?;
+        self.set_ram_counter(spi, delay, 0, 0)?;
 
         self.command(spi, Command::WriteRam)?;
         self.interface.data_x_times(
@@ -339,7 +352,7 @@ where
         // Always keep the base buffer equals to current if not doing partial refresh.
         if self.refresh == RefreshLut::Full {
             self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
-            self.set_ram_address_counters(spi, delay, 0, 0)?;
+            self.set_ram_counter(spi, delay, 0, 0)?;
 
             self.command(spi, Command::WriteRamRed)?;
             self.interface.data_x_times(
@@ -406,9 +419,10 @@ where
     ) -> Result<(), SPI::Error> {
         assert!(buffer_len(WIDTH as usize, HEIGHT as usize) == buffer.len());
         self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
-        self.set_ram_address_counters(spi, delay, 0, 0)?;
+        self.set_ram_counter(spi, delay, 0, 0)?;
 
-        self.cmd_with_data(spi, Command::WriteRamRed, buffer)?;
+        self.interface
+            .cmd_with_rows(spi, Command::WriteRamRed, buffer, WIDTH)?;
         Ok(())
     }
 
@@ -528,8 +542,16 @@ where
         self.cmd_with_data(spi, Command::DataEntryModeSetting, &[mode])
     }
 
-    /// Sets both X and Y pixels ranges
-    fn set_ram_area(
+    /// Sets the window of the RAM the following writes go to
+    ///
+    /// Both ends are included. `start_x` and `end_x` are in pixels, but only whole bytes
+    /// can be addressed, so their lowest 3 bits are ignored. Panics if a start is behind
+    /// its end.
+    ///
+    /// Works like [Epd2in9::set_ram_area](crate::epd2in9::Epd2in9::set_ram_area), see there
+    /// for how RAM windows are used for partial updates. Set the start of the window with
+    /// [set_ram_counter](Epd2in13::set_ram_counter) before writing.
+    pub fn set_ram_area(
         &mut self,
         spi: &mut SPI,
         start_x: u32,
@@ -537,6 +559,9 @@ where
         end_x: u32,
         end_y: u32,
     ) -> Result<(), SPI::Error> {
+        assert!(start_x <= end_x);
+        assert!(start_y <= end_y);
+
         self.cmd_with_data(
             spi,
             Command::SetRamXAddressStartEndPosition,
@@ -555,8 +580,11 @@ where
         )
     }
 
-    /// Sets both X and Y pixels counters when writing data to RAM
-    fn set_ram_address_counters(
+    /// Sets the position in the RAM the next write starts at
+    ///
+    /// `x` is in pixels, its lowest 3 bits are ignored. See
+    /// [set_ram_area](Epd2in13::set_ram_area).
+    pub fn set_ram_counter(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -598,4 +626,30 @@ mod tests {
         assert_eq!(HEIGHT, 250);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[test]
+    fn padding_bits_cleared() {
+        use crate::recording::*;
+        use core::cell::RefCell;
+
+        let mut buffer = [0u8; 1024];
+        let recorder = RefCell::new(Recorder::new(&mut buffer));
+        let mut spi = RecordingSpi(&recorder);
+        let mut delay = RecordingDelay(&recorder);
+        let (busy, dc, rst) = (
+            RecordingBusy(&recorder),
+            RecordingDc(&recorder),
+            RecordingRst(&recorder),
+        );
+        let mut epd = Epd2in13::new(&mut spi, NoPin, busy, dc, rst, &mut delay, None).unwrap();
+
+        // rows of 10 pixels take 2 bytes, the last 6 bits of each row are padding
+        let frame = [0xFF; 4];
+        epd.update_partial_frame(&mut spi, &mut delay, &frame, 8, 0, 10, 2)
+            .unwrap();
+        let recorder = recorder.into_inner();
+        let stream = recorder.stream().unwrap();
+        let write_ram = [OP_COMMAND, 0x24, OP_DATA, 4, 0, 0xFF, 0xC0, 0xFF, 0xC0];
+        assert!(stream.windows(write_ram.len()).any(|op| op == write_ram));
+    }
 }
@@ -66,6 +66,10 @@ pub const WIDTH: u32 = 104;
 pub const HEIGHT: u32 = 212;
 /// Default background color (white) of epd2in13bc display
 pub const DEFAULT_BACKGROUND_COLOR: TriColor = TriColor::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Highest SPI clock speed the display was tested with
+pub const SPI_MAX_SPEED_HZ: u32 = 10_000_000;
 
 /// Number of bits for b/w buffer and same for chromatic buffer
 const NUM_DISPLAY_BITS: u32 = WIDTH * HEIGHT / 8;
@@ -268,6 +272,7 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.interface.cmd(spi, Command::DataStartTransmission1)?;
 
         self.interface.data(spi, buffer)?;
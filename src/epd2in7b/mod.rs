@@ -9,7 +9,7 @@ use embedded_hal::{
 
 use crate::interface::DisplayInterface;
 use crate::traits::{
-    InternalWiAdditions, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+    InternalWiAdditions, RefreshLut, SupportsPartial, WaveshareDisplay, WaveshareThreeColorDisplay,
 };
 
 // The Lookup Tables for the Display
@@ -22,6 +22,10 @@ pub const WIDTH: u32 = 176;
 pub const HEIGHT: u32 = 264;
 /// Default Background Color
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Conservative default SPI clock speed, not tested with this display
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 const IS_BUSY_LOW: bool = true;
 
 use crate::color::Color;
@@ -115,6 +119,8 @@ where
     }
 }
 
+impl<SPI, CS, BUSY, DC, RST, DELAY> SupportsPartial for Epd2in7b<SPI, CS, BUSY, DC, RST, DELAY> {}
+
 impl<SPI, CS, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, CS, BUSY, DC, RST, DELAY>
     for Epd2in7b<SPI, CS, BUSY, DC, RST, DELAY>
 where
@@ -167,6 +173,7 @@ where
         buffer: &[u8],
         _delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.interface.cmd(spi, Command::DataStartTransmission1)?;
         self.send_buffer_helper(spi, buffer)?;
 
@@ -189,6 +196,7 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(width as usize, height as usize));
         self.interface
             .cmd(spi, Command::PartialDataStartTransmission1)?;
 
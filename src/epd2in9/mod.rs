@@ -47,6 +47,10 @@ pub const WIDTH: u32 = 128;
 pub const HEIGHT: u32 = 296;
 /// Default Background Color (white)
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Conservative default SPI clock speed, not tested with this display
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 const IS_BUSY_LOW: bool = false;
 
 use embedded_hal::{
@@ -138,6 +142,8 @@ where
     }
 }
 
+impl<SPI, CS, BUSY, DC, RST, DELAY> SupportsPartial for Epd2in9<SPI, CS, BUSY, DC, RST, DELAY> {}
+
 impl<SPI, CS, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, CS, BUSY, DC, RST, DELAY>
     for Epd2in9<SPI, CS, BUSY, DC, RST, DELAY>
 where
@@ -200,6 +206,7 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.wait_until_idle(spi, delay)?;
         self.use_full_frame(spi, delay)?;
 
@@ -219,12 +226,16 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(width as usize, height as usize));
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
         self.wait_until_idle(spi, delay)?;
-        self.set_ram_area(spi, x, y, x + width, y + height)?;
+        self.set_ram_area(spi, x, y, x + width - 1, y + height - 1)?;
         self.set_ram_counter(spi, delay, x, y)?;
 
         self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
+            .cmd_with_rows(spi, Command::WriteRam, buffer, width)?;
         Ok(())
     }
 
@@ -312,7 +323,17 @@ where
         self.set_ram_counter(spi, delay, 0, 0)
     }
 
-    fn set_ram_area(
+    /// Sets the window of the RAM the following writes go to
+    ///
+    /// Both ends are included. `start_x` and `end_x` are in pixels, but only whole bytes
+    /// can be addressed, so their lowest 3 bits are ignored. Panics if a start is behind
+    /// its end.
+    ///
+    /// This controller uses RAM windows for partial updates instead of the partial window
+    /// mode of e.g. the 4.2" display: The window only restricts where data is written,
+    /// the following refresh still covers the whole display. Set the start of the window
+    /// with [set_ram_counter](Epd2in9::set_ram_counter) before writing.
+    pub fn set_ram_area(
         &mut self,
         spi: &mut SPI,
         start_x: u32,
@@ -320,8 +341,8 @@ where
         end_x: u32,
         end_y: u32,
     ) -> Result<(), SPI::Error> {
-        assert!(start_x < end_x);
-        assert!(start_y < end_y);
+        assert!(start_x <= end_x);
+        assert!(start_y <= end_y);
 
         // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
         // aren't relevant
@@ -344,7 +365,11 @@ where
         )
     }
 
-    fn set_ram_counter(
+    /// Sets the position in the RAM the next write starts at
+    ///
+    /// `x` is in pixels, its lowest 3 bits are ignored. See
+    /// [set_ram_area](Epd2in9::set_ram_area).
+    pub fn set_ram_counter(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
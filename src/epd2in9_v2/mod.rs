@@ -60,6 +60,10 @@ pub const WIDTH: u32 = 128;
 pub const HEIGHT: u32 = 296;
 /// Default Background Color (white)
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Conservative default SPI clock speed, not tested with this display
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 const IS_BUSY_LOW: bool = false;
 
 const LUT_PARTIAL_2IN9: [u8; 159] = [
@@ -211,6 +215,7 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.wait_until_idle(spi, delay)?;
         self.interface.cmd_with_data(spi, Command::WriteRam, buffer)
     }
@@ -225,13 +230,14 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(width as usize, height as usize));
         //TODO This is copied from epd2in9 but it seems not working. Partial refresh supported by version 2?
         self.wait_until_idle(spi, delay)?;
         self.set_ram_area(spi, x, y, x + width, y + height)?;
         self.set_ram_counter(spi, delay, x, y)?;
 
         self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
+            .cmd_with_rows(spi, Command::WriteRam, buffer, width)?;
         Ok(())
     }
 
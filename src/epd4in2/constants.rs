@@ -1,6 +1,8 @@
 //! This file contains look-up-tables used to set voltages used during
 //! various categories of pixel refreshes.
 
+use super::LutSet;
+
 #[rustfmt::skip]
 pub(crate) const LUT_VCOM0: [u8; 44] = [
 // The commented-out line below was used in a Ben Krasnow video explaining
@@ -113,3 +115,109 @@ pub(crate) const LUT_WB_QUICK: [u8; 42] =[
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
 ];
+
+// The fast LUTs only consist of the last phase group of the full LUTs above: A single
+// group driving every pixel directly to its new color, which is done twice.
+// Compared to the quick LUTs (same group, but only once) this leaves less ghosting,
+// while it still avoids the flashing of the first groups of the full LUTs.
+
+#[rustfmt::skip]
+pub(crate) const LUT_VCOM0_FAST: [u8; 44] = [
+    0x00, 0x0E, 0x0E, 0x00, 0x00, 0x02,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[rustfmt::skip]
+pub(crate) const LUT_WW_FAST: [u8; 42] =[
+    0xA0, 0x0E, 0x0E, 0x00, 0x00, 0x02,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[rustfmt::skip]
+pub(crate) const LUT_BW_FAST: [u8; 42] =[
+    0xA0, 0x0E, 0x0E, 0x00, 0x00, 0x02,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[rustfmt::skip]
+pub(crate) const LUT_BB_FAST: [u8; 42] =[
+    0x50, 0x0E, 0x0E, 0x00, 0x00, 0x02,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[rustfmt::skip]
+pub(crate) const LUT_WB_FAST: [u8; 42] =[
+    0x50, 0x0E, 0x0E, 0x00, 0x00, 0x02,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+// Used for the transitions which shouldn't change the pixel at all: No phase group is
+// set up, so these pixels aren't driven during the refresh. Combined with the fast
+// LUTs for the transitions to black, this only ever adds black pixels.
+#[rustfmt::skip]
+pub(crate) const LUT_KEEP: [u8; 42] =[
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+pub(crate) const LUTS_FULL: LutSet = LutSet {
+    vcom: LUT_VCOM0,
+    ww: LUT_WW,
+    bw: LUT_BW,
+    wb: LUT_WB,
+    bb: LUT_BB,
+};
+
+pub(crate) const LUTS_QUICK: LutSet = LutSet {
+    vcom: LUT_VCOM0_QUICK,
+    ww: LUT_WW_QUICK,
+    bw: LUT_BW_QUICK,
+    wb: LUT_WB_QUICK,
+    bb: LUT_BB_QUICK,
+};
+
+pub(crate) const LUTS_FAST: LutSet = LutSet {
+    vcom: LUT_VCOM0_FAST,
+    ww: LUT_WW_FAST,
+    bw: LUT_BW_FAST,
+    wb: LUT_WB_FAST,
+    bb: LUT_BB_FAST,
+};
+
+pub(crate) const LUTS_ACCUMULATE: LutSet = LutSet {
+    vcom: LUT_VCOM0_FAST,
+    ww: LUT_KEEP,
+    bw: LUT_KEEP,
+    wb: LUT_WB_FAST,
+    bb: LUT_BB_FAST,
+};
@@ -45,7 +45,41 @@
 //!# }
 //!```
 //!
+//! # Several partial windows in one refresh
 //!
+//! [update_partial_frame](WaveshareDisplay::update_partial_frame) only transmits its window
+//! into the memory of the display and doesn't refresh. Several windows, e.g. of different
+//! widgets, can therefore be transmitted one after another and shown together with a single
+//! [display_frame](WaveshareDisplay::display_frame):
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::*;
+//!# fn main() -> Result<(), MockError> {
+//!use epd_waveshare::{epd4in2::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let cs_pin = pin::Mock::new(&expectations);
+//!# let busy_in = pin::Mock::new(&expectations);
+//!# let dc = pin::Mock::new(&expectations);
+//!# let rst = pin::Mock::new(&expectations);
+//!# let mut delay = delay::MockNoop::new();
+//!# let mut epd = Epd4in2::new(&mut spi, cs_pin, busy_in, dc, rst, &mut delay, None)?;
+//!let clock = [0xFF; 64 / 8 * 16];
+//!let battery = [0x00; 16 / 8 * 8];
+//!
+//!epd.update_partial_frame(&mut spi, &mut delay, &clock, 0, 0, 64, 16)?;
+//!epd.update_partial_frame(&mut spi, &mut delay, &battery, 384, 0, 16, 8)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+//!
+//! The controller ignores the lowest 3 bits of the horizontal window borders, so an x
+//! position or width that isn't a multiple of 8 widens the window to whole bytes. The
+//! buffer always covers the window returned by [aligned_window], so draw it accordingly.
+//! The windows shouldn't overlap, the last one transmitted wins.
 //!
 //! BE CAREFUL! The screen can get ghosting/burn-ins through the Partial Fast Update Drawing.
 
@@ -54,8 +88,10 @@ use embedded_hal::{
     digital::v2::*,
 };
 
-use crate::interface::DisplayInterface;
-use crate::traits::{InternalWiAdditions, QuickRefresh, RefreshLut, WaveshareDisplay};
+use crate::interface::{BusyToggle, DisplayInterface};
+use crate::traits::{
+    InternalWiAdditions, QuickRefresh, RefreshLut, SupportsPartial, WaveshareDisplay,
+};
 
 //The Lookup Tables for the Display
 mod constants;
@@ -67,13 +103,39 @@ pub const WIDTH: u32 = 400;
 pub const HEIGHT: u32 = 300;
 /// Default Background Color
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Highest SPI clock speed the display was tested with
+pub const SPI_MAX_SPEED_HZ: u32 = 8_000_000;
 const IS_BUSY_LOW: bool = true;
 
+/// Returns the x position and width of the partial window covering `width` pixels from `x`
+///
+/// The controller ignores the lowest 3 bits of the horizontal window borders, so the
+/// window starts at `x` rounded down to a multiple of 8 and ends at the end of the byte
+/// containing the last pixel. A partial update with another window is shifted
+/// accordingly, e.g. the window 5, 8 becomes 0, 16: its buffer needs to be 16 pixels
+/// wide and the region is drawn 5 pixels into it.
+pub fn aligned_window(x: u32, width: u32) -> (u32, u32) {
+    let start = x & !0x07;
+    match width {
+        0 => (start, 0),
+        _ => (start, ((x + width - 1) | 0x07) + 1 - start),
+    }
+}
+
+// size of the buffer for the window returned by aligned_window
+fn window_buffer_len(x: u32, width: u32, height: u32) -> usize {
+    let (_, window_width) = aligned_window(x, width);
+    buffer_len(window_width as usize, height as usize)
+}
+
 use crate::color::Color;
 
 pub(crate) mod command;
 use self::command::Command;
 use crate::buffer_len;
+use crate::pixelbuf::get_bit;
 
 /// Full size buffer for use with the 4in2 EPD
 #[cfg(feature = "graphics")]
@@ -85,6 +147,70 @@ pub type Display4in2 = crate::graphics::Display<
     Color,
 >;
 
+/// Set of the look-up tables (LUTs) defining the waveforms of a refresh
+///
+/// Each table is named after the transition of a pixel it is used for, e.g. `bw` for
+/// pixels going from black to white.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LutSet {
+    /// LUT for the Vcom voltage
+    pub vcom: [u8; 44],
+    /// LUT for white to white
+    pub ww: [u8; 42],
+    /// LUT for black to white
+    pub bw: [u8; 42],
+    /// LUT for white to black
+    pub wb: [u8; 42],
+    /// LUT for black to black
+    pub bb: [u8; 42],
+}
+
+/// Where the display takes the LUTs for a refresh from
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LutSource {
+    /// The waveforms programmed by the manufacturer into the OTP memory of the display
+    Otp,
+    /// The LUTs sent by the driver (default)
+    #[default]
+    Registers,
+}
+
+/// Patterns of [Epd4in2::display_test_pattern] for bringing up a display
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TestPattern {
+    /// Black and white squares of 8x8 pixels, starting with a black one at the top left
+    Checkerboard,
+    /// Black and white stripes 8 pixels wide, starting with a black one at the left
+    VerticalStripes,
+    /// Dithered from black at the left to white at the right
+    Gradient,
+    /// A black border of 4 pixels and a black 32x32 square in the top left corner,
+    /// showing which way is up
+    Border,
+    /// Every pixel black
+    AllBlack,
+    /// Every pixel white
+    AllWhite,
+}
+
+impl TestPattern {
+    fn is_white(self, x: u32, y: u32) -> bool {
+        // ordered dither thresholds of 4x4 pixels, in 16ths
+        const BAYER: [[u32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+        match self {
+            TestPattern::Checkerboard => (x / 8 + y / 8) % 2 == 1,
+            TestPattern::VerticalStripes => (x / 8) % 2 == 1,
+            TestPattern::Gradient => x * 16 / WIDTH > BAYER[y as usize % 4][x as usize % 4],
+            TestPattern::Border => {
+                let border = x < 4 || y < 4 || x >= WIDTH - 4 || y >= HEIGHT - 4;
+                !(border || (x < 32 && y < 32))
+            }
+            TestPattern::AllBlack => false,
+            TestPattern::AllWhite => true,
+        }
+    }
+}
+
 /// Epd4in2 driver
 ///
 pub struct Epd4in2<SPI, CS, BUSY, DC, RST, DELAY> {
@@ -94,6 +220,32 @@ pub struct Epd4in2<SPI, CS, BUSY, DC, RST, DELAY> {
     color: Color,
     /// Refresh LUT
     refresh: RefreshLut,
+    /// LUTs currently loaded into the display, None if unknown
+    lut: Option<LutSet>,
+    /// Additional time to wait after a refresh has finished
+    refresh_settle_us: u32,
+    /// Time to wait for the busy pin to assert after starting a refresh, 0 to not wait
+    busy_confirm_us: u32,
+    /// Vcom and data interval, lower nibble of VcomAndDataIntervalSetting
+    data_interval: u8,
+    /// Inverted data polarity
+    inverted: bool,
+    /// Source of the LUTs used for refreshes
+    lut_source: LutSource,
+    /// A refresh was started and its duration wasn't taken yet
+    refreshing: bool,
+    /// In deep sleep since `sleep`, until the next `init`
+    sleeping: bool,
+    /// Clear the display at the end of `init`
+    clear_on_init: bool,
+    /// Don't fill the old frame in `update_frame`
+    skip_old_frame: bool,
+    /// Time the display was busy during the last refresh
+    last_refresh_us: u32,
+    /// Reversed gate scan direction (bottom to top)
+    gate_reversed: bool,
+    /// Reversed source shift direction (right to left)
+    source_reversed: bool,
 }
 
 impl<SPI, CS, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, CS, BUSY, DC, RST, DELAY>
@@ -107,19 +259,23 @@ where
     DELAY: DelayUs<u32>,
 {
     fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        // reset the device
+        // reset the device, which also wakes it up from deep sleep
         self.interface.reset(delay, 10_000, 10_000);
+        self.sleeping = false;
 
         // set the power settings
-        self.interface.cmd_with_data(
+        self.interface.send_command_with_data(
             spi,
             Command::PowerSetting,
             &[0x03, 0x00, 0x2b, 0x2b, 0xff],
         )?;
 
         // start the booster
-        self.interface
-            .cmd_with_data(spi, Command::BoosterSoftStart, &[0x17, 0x17, 0x17])?;
+        self.interface.send_command_with_data(
+            spi,
+            Command::BoosterSoftStart,
+            &[0x17, 0x17, 0x17],
+        )?;
 
         // power on
         self.command(spi, Command::PowerOn)?;
@@ -127,30 +283,36 @@ where
         self.wait_until_idle(spi, delay)?;
 
         // set the panel settings
-        self.cmd_with_data(spi, Command::PanelSetting, &[0x3F])?;
+        self.send_panel_setting(spi)?;
 
         // Set Frequency, 200 Hz didn't work on my board
         // 150Hz and 171Hz wasn't tested yet
         // TODO: Test these other frequencies
         // 3A 100HZ   29 150Hz 39 200HZ  31 171HZ DEFAULT: 3c 50Hz
-        self.cmd_with_data(spi, Command::PllControl, &[0x3A])?;
+        self.send_command_with_data(spi, Command::PllControl, &[0x3A])?;
 
         self.send_resolution(spi)?;
 
         self.interface
-            .cmd_with_data(spi, Command::VcmDcSetting, &[0x12])?;
+            .send_command_with_data(spi, Command::VcmDcSetting, &[0x12])?;
 
         //VBDF 17|D7 VBDW 97  VBDB 57  VBDF F7  VBDW 77  VBDB 37  VBDR B7
-        self.interface
-            .cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x97])?;
+        self.send_vcom_and_data_interval(spi)?;
 
         self.set_lut(spi, delay, None)?;
 
         self.wait_until_idle(spi, delay)?;
+
+        if self.clear_on_init {
+            self.clear_frame(spi, delay)?;
+            self.display_frame(spi, delay)?;
+        }
         Ok(())
     }
 }
 
+impl<SPI, CS, BUSY, DC, RST, DELAY> SupportsPartial for Epd4in2<SPI, CS, BUSY, DC, RST, DELAY> {}
+
 impl<SPI, CS, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, CS, BUSY, DC, RST, DELAY>
     for Epd4in2<SPI, CS, BUSY, DC, RST, DELAY>
 where
@@ -171,14 +333,7 @@ where
         delay: &mut DELAY,
         delay_us: Option<u32>,
     ) -> Result<Self, SPI::Error> {
-        let interface = DisplayInterface::new(cs, busy, dc, rst, delay_us);
-        let color = DEFAULT_BACKGROUND_COLOR;
-
-        let mut epd = Epd4in2 {
-            interface,
-            color,
-            refresh: RefreshLut::Full,
-        };
+        let mut epd = Self::new_uninitialized(cs, busy, dc, rst, delay_us);
 
         epd.init(spi, delay)?;
 
@@ -186,21 +341,23 @@ where
     }
 
     fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
         self.wait_until_idle(spi, delay)?;
         self.interface
-            .cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x17])?; //border floating
+            .send_command_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x17])?; //border floating
         self.command(spi, Command::VcmDcSetting)?; // VCOM to 0V
         self.command(spi, Command::PanelSetting)?;
 
-        self.command(spi, Command::PowerSetting)?; //VG&VS to 0V fast
-        for _ in 0..4 {
-            self.send_data(spi, &[0x00])?;
-        }
+        //VG&VS to 0V fast
+        self.send_command_with_data(spi, Command::PowerSetting, &[0x00; 4])?;
 
         self.command(spi, Command::PowerOff)?;
         self.wait_until_idle(spi, delay)?;
         self.interface
-            .cmd_with_data(spi, Command::DeepSleep, &[0xA5])?;
+            .send_command_with_data(spi, Command::DeepSleep, &[0xA5])?;
+        self.sleeping = true;
         Ok(())
     }
 
@@ -224,24 +381,49 @@ where
         HEIGHT
     }
 
+    /// Does nothing while the display is asleep, see [is_sleeping](Epd4in2::is_sleeping)
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` doesn't have the size of a full frame, see
+    /// [buffer_size](Epd4in2::buffer_size).
     fn update_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
+        assert!(buffer.len() == self.buffer_size());
         self.wait_until_idle(spi, delay)?;
         let color_value = self.color.get_byte_value();
 
-        self.interface.cmd(spi, Command::DataStartTransmission1)?;
-        self.interface
-            .data_x_times(spi, color_value, WIDTH / 8 * HEIGHT)?;
+        if !self.skip_old_frame {
+            self.interface.cmd_with_data_x_times(
+                spi,
+                delay,
+                Command::DataStartTransmission1,
+                color_value,
+                self.buffer_size() as u32,
+            )?;
+        }
 
         self.interface
-            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission2, buffer)?;
         Ok(())
     }
 
+    /// `buffer` covers the window returned by [aligned_window] for `x` and `width`, e.g.
+    /// it is 16 pixels wide for the region 5, 8.
+    ///
+    /// Does nothing while the display is asleep or if the region is empty, see
+    /// [is_sleeping](Epd4in2::is_sleeping)
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` doesn't have the size of the window returned by [aligned_window].
     fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
@@ -252,47 +434,37 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        if buffer.len() as u32 != width / 8 * height {
-            //TODO: panic!! or sth like that
-            //return Err("Wrong buffersize");
+        if self.sleeping || width == 0 || height == 0 {
+            return Ok(());
         }
+        self.wait_until_idle(spi, delay)?;
+        assert!(buffer.len() == window_buffer_len(x, width, height));
 
         self.command(spi, Command::PartialIn)?;
         self.command(spi, Command::PartialWindow)?;
-        self.send_data(spi, &[(x >> 8) as u8])?;
-        let tmp = x & 0xf8;
-        self.send_data(spi, &[tmp as u8])?; // x should be the multiple of 8, the last 3 bit will always be ignored
-        let tmp = tmp + width - 1;
-        self.send_data(spi, &[(tmp >> 8) as u8])?;
-        self.send_data(spi, &[(tmp | 0x07) as u8])?;
-
-        self.send_data(spi, &[(y >> 8) as u8])?;
-        self.send_data(spi, &[y as u8])?;
-
-        self.send_data(spi, &[((y + height - 1) >> 8) as u8])?;
-        self.send_data(spi, &[(y + height - 1) as u8])?;
-
-        self.send_data(spi, &[0x01])?; // Gates scan both inside and outside of the partial window. (default)
+        self.shift_display(spi, x, y, width, height)?;
 
         //TODO: handle dtm somehow
         let is_dtm1 = false;
-        if is_dtm1 {
-            self.command(spi, Command::DataStartTransmission1)? //TODO: check if data_start transmission 1 also needs "old"/background data here
+        let dtm = if is_dtm1 {
+            Command::DataStartTransmission1 //TODO: check if data_start transmission 1 also needs "old"/background data here
         } else {
-            self.command(spi, Command::DataStartTransmission2)?
-        }
+            Command::DataStartTransmission2
+        };
 
-        self.send_data(spi, buffer)?;
+        self.interface.cmd_with_buffer(spi, delay, dtm, buffer)?;
 
         self.command(spi, Command::PartialOut)?;
         Ok(())
     }
 
+    /// Does nothing while the display is asleep, see [is_sleeping](Epd4in2::is_sleeping)
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
         self.wait_until_idle(spi, delay)?;
-        self.command(spi, Command::DisplayRefresh)?;
-        Ok(())
+        self.refresh(spi, delay)
     }
 
     fn update_and_display_frame(
@@ -302,26 +474,41 @@ where
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
         self.update_frame(spi, buffer, delay)?;
-        self.command(spi, Command::DisplayRefresh)?;
-        Ok(())
+        self.refresh(spi, delay)
     }
 
+    /// Does nothing while the display is asleep, see [is_sleeping](Epd4in2::is_sleeping)
     fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
         self.wait_until_idle(spi, delay)?;
         self.send_resolution(spi)?;
 
         let color_value = self.color.get_byte_value();
 
-        self.interface.cmd(spi, Command::DataStartTransmission1)?;
-        self.interface
-            .data_x_times(spi, color_value, WIDTH / 8 * HEIGHT)?;
+        self.interface.cmd_with_data_x_times(
+            spi,
+            delay,
+            Command::DataStartTransmission1,
+            color_value,
+            self.buffer_size() as u32,
+        )?;
 
-        self.interface.cmd(spi, Command::DataStartTransmission2)?;
-        self.interface
-            .data_x_times(spi, color_value, WIDTH / 8 * HEIGHT)?;
+        self.interface.cmd_with_data_x_times(
+            spi,
+            delay,
+            Command::DataStartTransmission2,
+            color_value,
+            self.buffer_size() as u32,
+        )?;
         Ok(())
     }
 
+    /// The LUTs are only transmitted if they differ from the ones already loaded into
+    /// the display, so switching to the same LUT again doesn't cost any SPI traffic.
+    ///
+    /// Using `None` always transmits the selected LUTs once more.
     fn set_lut(
         &mut self,
         spi: &mut SPI,
@@ -331,24 +518,25 @@ where
         if let Some(refresh_lut) = refresh_rate {
             self.refresh = refresh_lut;
         }
-        match self.refresh {
-            RefreshLut::Full => {
-                self.set_lut_helper(spi, delay, &LUT_VCOM0, &LUT_WW, &LUT_BW, &LUT_WB, &LUT_BB)
-            }
-            RefreshLut::Quick => self.set_lut_helper(
-                spi,
-                delay,
-                &LUT_VCOM0_QUICK,
-                &LUT_WW_QUICK,
-                &LUT_BW_QUICK,
-                &LUT_WB_QUICK,
-                &LUT_BB_QUICK,
-            ),
+        if self.lut_source == LutSource::Otp {
+            return Ok(());
+        }
+        let lut = match self.refresh {
+            RefreshLut::Full => &LUTS_FULL,
+            RefreshLut::Quick => &LUTS_QUICK,
+        };
+        if refresh_rate.is_some() && self.lut.as_ref() == Some(lut) {
+            return Ok(());
         }
+        self.set_lut_helper(spi, delay, lut)
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
         self.interface.wait_until_idle(delay, IS_BUSY_LOW);
+        if self.refreshing {
+            self.refreshing = false;
+            self.last_refresh_us = self.interface.take_waited_us();
+        }
         Ok(())
     }
 }
@@ -370,179 +558,336 @@ where
         self.interface.data(spi, data)
     }
 
-    fn cmd_with_data(
+    fn send_command_with_data(
         &mut self,
         spi: &mut SPI,
         command: Command,
         data: &[u8],
     ) -> Result<(), SPI::Error> {
-        self.interface.cmd_with_data(spi, command, data)
+        self.interface.send_command_with_data(spi, command, data)
+    }
+
+    /// Starts the refresh of the display
+    ///
+    /// The busy pin is the real synchronisation: every following command first waits
+    /// until the display is idle again. Only if a settle time was set, this already waits
+    /// for the refresh to finish and adds the settle time on top.
+    ///
+    /// With a busy confirm window set, the display has to be idle before the refresh is
+    /// started and the refresh only counts as started once the busy pin asserted.
+    fn refresh(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
+        if self.busy_confirm_us > 0 {
+            self.wait_until_idle(spi, delay)?;
+        }
+        self.command(spi, Command::DisplayRefresh)?;
+        self.interface.take_waited_us();
+        self.refreshing = true;
+        if self.busy_confirm_us > 0 {
+            // a missing busy phase is tolerated, the timeout is all the driver can do
+            let _ = self
+                .interface
+                .wait_until_busy(delay, IS_BUSY_LOW, self.busy_confirm_us);
+        }
+        if self.refresh_settle_us > 0 {
+            self.wait_until_idle(spi, delay)?;
+            delay.delay_us(self.refresh_settle_us);
+        }
+        Ok(())
+    }
+
+    fn send_panel_setting(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+        // 400x300, B/W, scan up (UD = 1), shift right (SHL = 1), booster on, no reset
+        // and LUT from register (REG = 1) or from OTP (REG = 0)
+        let mut value = match self.lut_source {
+            LutSource::Registers => 0x3F,
+            LutSource::Otp => 0x1F,
+        };
+        if self.gate_reversed {
+            value &= !0x08;
+        }
+        if self.source_reversed {
+            value &= !0x04;
+        }
+        self.send_command_with_data(spi, Command::PanelSetting, &[value])
+    }
+
+    fn send_vcom_and_data_interval(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+        // white border (VBD = 10), default data polarity (DDX = 01) or inverted (DDX = 00)
+        let ddx = if self.inverted { 0x00 } else { 0x10 };
+        let value = 0x80 | ddx | self.data_interval;
+        self.send_command_with_data(spi, Command::VcomAndDataIntervalSetting, &[value])
     }
 
     fn send_resolution(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
         let w = self.width();
         let h = self.height();
 
-        self.command(spi, Command::ResolutionSetting)?;
-        self.send_data(spi, &[(w >> 8) as u8])?;
-        self.send_data(spi, &[w as u8])?;
-        self.send_data(spi, &[(h >> 8) as u8])?;
-        self.send_data(spi, &[h as u8])
+        self.send_command_with_data(
+            spi,
+            Command::ResolutionSetting,
+            &[(w >> 8) as u8, w as u8, (h >> 8) as u8, h as u8],
+        )
     }
 
-    #[allow(clippy::too_many_arguments)]
     fn set_lut_helper(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
-        lut_vcom: &[u8],
-        lut_ww: &[u8],
-        lut_bw: &[u8],
-        lut_wb: &[u8],
-        lut_bb: &[u8],
+        lut: &LutSet,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
+        // forget the old LUTs, in case sending fails halfway
+        self.lut = None;
+
         // LUT VCOM
-        self.cmd_with_data(spi, Command::LutForVcom, lut_vcom)?;
+        self.send_command_with_data(spi, Command::LutForVcom, &lut.vcom)?;
 
         // LUT WHITE to WHITE
-        self.cmd_with_data(spi, Command::LutWhiteToWhite, lut_ww)?;
+        self.send_command_with_data(spi, Command::LutWhiteToWhite, &lut.ww)?;
 
         // LUT BLACK to WHITE
-        self.cmd_with_data(spi, Command::LutBlackToWhite, lut_bw)?;
+        self.send_command_with_data(spi, Command::LutBlackToWhite, &lut.bw)?;
 
         // LUT WHITE to BLACK
-        self.cmd_with_data(spi, Command::LutWhiteToBlack, lut_wb)?;
+        self.send_command_with_data(spi, Command::LutWhiteToBlack, &lut.wb)?;
 
         // LUT BLACK to BLACK
-        self.cmd_with_data(spi, Command::LutBlackToBlack, lut_bb)?;
+        self.send_command_with_data(spi, Command::LutBlackToBlack, &lut.bb)?;
+
+        self.lut = Some(*lut);
         Ok(())
     }
 
-    /// Helper function. Sets up the display to send pixel data to a custom
-    /// starting point.
-    pub fn shift_display(
-        &mut self,
-        spi: &mut SPI,
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
-    ) -> Result<(), SPI::Error> {
-        self.send_data(spi, &[(x >> 8) as u8])?;
-        let tmp = x & 0xf8;
-        self.send_data(spi, &[tmp as u8])?; // x should be the multiple of 8, the last 3 bit will always be ignored
-        let tmp = tmp + width - 1;
-        self.send_data(spi, &[(tmp >> 8) as u8])?;
-        self.send_data(spi, &[(tmp | 0x07) as u8])?;
-
-        self.send_data(spi, &[(y >> 8) as u8])?;
-        self.send_data(spi, &[y as u8])?;
-
-        self.send_data(spi, &[((y + height - 1) >> 8) as u8])?;
-        self.send_data(spi, &[(y + height - 1) as u8])?;
+    /// Creates the driver without initializing the display
+    ///
+    /// Unlike [new](WaveshareDisplay::new) nothing is sent to the display. This allows
+    /// changing settings on the struct before the display is initialized the first time,
+    /// e.g. with [set_refresh_settle_us](Epd4in2::set_refresh_settle_us). The settings
+    /// sent to the display by their setters (LUT source, data interval, inversion) are
+    /// applied again by the initialization.
+    ///
+    /// [wake_up](WaveshareDisplay::wake_up), which runs the whole initialization, must be
+    /// called before any other operation on the display.
+    pub fn new_uninitialized(cs: CS, busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        Epd4in2 {
+            interface: DisplayInterface::new(cs, busy, dc, rst, delay_us),
+            color: DEFAULT_BACKGROUND_COLOR,
+            refresh: RefreshLut::Full,
+            lut: None,
+            refresh_settle_us: 0,
+            busy_confirm_us: 0,
+            data_interval: 0x07,
+            inverted: false,
+            lut_source: LutSource::Registers,
+            refreshing: false,
+            sleeping: false,
+            clear_on_init: false,
+            skip_old_frame: false,
+            last_refresh_us: 0,
+            gate_reversed: false,
+            source_reversed: false,
+        }
+    }
 
-        self.send_data(spi, &[0x01])?; // Gates scan both inside and outside of the partial window. (default)
+    /// Returns a copy of the LUTs last loaded into the display
+    ///
+    /// The LUTs can't be read back from the display, this is the cached copy of what
+    /// was sent. `None` if nothing was loaded yet or sending the LUTs failed.
+    pub fn current_lut(&self) -> Option<&LutSet> {
+        self.lut.as_ref()
+    }
 
-        Ok(())
+    /// Returns the number of bytes needed by a full frame buffer
+    ///
+    /// Every function transmitting a full frame checks its buffer against this size.
+    pub fn buffer_size(&self) -> usize {
+        buffer_len(WIDTH as usize, HEIGHT as usize)
     }
-}
 
-impl<SPI, CS, BUSY, DC, RST, DELAY> QuickRefresh<SPI, CS, BUSY, DC, RST, DELAY>
-    for Epd4in2<SPI, CS, BUSY, DC, RST, DELAY>
-where
-    SPI: Write<u8>,
-    CS: OutputPin,
-    BUSY: InputPin,
-    DC: OutputPin,
-    RST: OutputPin,
-    DELAY: DelayUs<u32>,
-{
-    /// To be followed immediately after by `update_old_frame`.
-    fn update_old_frame(
+    /// Transmits a full frame and displays it with the fast look-up table
+    ///
+    /// This is a middle ground between the full and the quick refresh: The display is
+    /// first quickly refreshed to white and afterwards the new frame is drawn. Both
+    /// passes use the fast LUT, which drives each pixel once directly to its new color
+    /// like the quick LUT does, but twice as long.
+    ///
+    /// - compared to the full refresh, it is faster and doesn't flash black and white
+    ///   several times
+    /// - compared to the quick refresh, it is slower but leaves far less ghosting
+    ///
+    /// The LUT selected with [set_lut](WaveshareDisplay::set_lut) is loaded again afterwards,
+    /// therefore this waits until the refresh is finished.
+    ///
+    /// Does nothing while the display is asleep, see [is_sleeping](Epd4in2::is_sleeping)
+    pub fn update_and_display_frame_fast(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
+        if self.sleeping {
+            return Ok(());
+        }
+        assert!(buffer.len() == self.buffer_size());
+        self.set_lut_helper(spi, delay, &LUTS_FAST)?;
 
-        self.interface.cmd(spi, Command::DataStartTransmission1)?;
+        // clear to white
+        self.interface.cmd_with_data_x_times(
+            spi,
+            delay,
+            Command::DataStartTransmission2,
+            Color::White.get_byte_value(),
+            self.buffer_size() as u32,
+        )?;
+        self.refresh(spi, delay)?;
+        self.wait_until_idle(spi, delay)?;
 
-        self.interface.data(spi, buffer)?;
+        // draw the new frame
+        self.interface
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission2, buffer)?;
+        self.refresh(spi, delay)?;
 
-        Ok(())
+        self.set_lut(spi, delay, None)
     }
 
-    /// To be used immediately after `update_old_frame`.
-    fn update_new_frame(
+    /// Transmits the old and the new frame and refreshes the display
+    ///
+    /// [update_frame](WaveshareDisplay::update_frame) fills the old frame (DTM1) with the
+    /// background color. Here `old` is sent to DTM1 and `new` to DTM2 instead, so the
+    /// refresh knows the actual transition of every pixel, e.g. the frame shown before.
+    /// With the quick LUT this gives a clean transition in a single refresh.
+    ///
+    /// Both buffers need to be of [buffer_size](Epd4in2::buffer_size).
+    ///
+    /// Does nothing while the display is asleep, see [is_sleeping](Epd4in2::is_sleeping)
+    pub fn display_frames(
         &mut self,
         spi: &mut SPI,
-        buffer: &[u8],
+        old: &[u8],
+        new: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
+        assert!(old.len() == self.buffer_size());
+        assert!(new.len() == self.buffer_size());
         self.wait_until_idle(spi, delay)?;
-        // self.send_resolution(spi)?;
 
-        self.interface.cmd(spi, Command::DataStartTransmission2)?;
-
-        self.interface.data(spi, buffer)?;
-
-        Ok(())
+        self.interface
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission1, old)?;
+        self.interface
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission2, new)?;
+        self.refresh(spi, delay)
     }
 
-    /// This is a wrapper around `display_frame` for using this device as a true
-    /// `QuickRefresh` device.
-    fn display_new_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.display_frame(spi, delay)
+    /// Transmits a frame drawn for a display mounted in portrait and refreshes the display
+    ///
+    /// `buffer` is a row-major buffer of `logical_width` x `logical_height` pixels, which
+    /// have to be [HEIGHT] x [WIDTH]. It is transposed on the fly while sending it, row by
+    /// row of the panel, so there is no need for a rotated copy of the buffer or a
+    /// [Display](crate::graphics::Display) rotated by software.
+    ///
+    /// The panel pixel at column `x` and row `y` is taken from the logical pixel at
+    /// column `y` and row `WIDTH - 1 - x`. This is the orientation of
+    /// [DisplayRotation::Rotate90](crate::graphics::DisplayRotation::Rotate90): the panel
+    /// rows are sent top to bottom, each one built from a logical column, walking that
+    /// column from its bottom to its top.
+    ///
+    /// Does nothing while the display is asleep, see [is_sleeping](Epd4in2::is_sleeping)
+    pub fn display_transposed(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        logical_width: u32,
+        logical_height: u32,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
+        assert!(logical_width == HEIGHT && logical_height == WIDTH);
+        let stride = buffer_len(logical_width as usize, 1);
+        assert!(buffer.len() == buffer_len(logical_width as usize, logical_height as usize));
+        self.display_pixels(spi, delay, |x, y| get_bit(buffer, stride, y, WIDTH - 1 - x))
     }
 
-    /// This is wrapper around `update_new_frame` and `display_frame` for using
-    /// this device as a true `QuickRefresh` device.
+    /// Shows a test pattern filling the whole display, e.g. to check the wiring
     ///
-    /// To be used immediately after `update_old_frame`.
-    fn update_and_display_new_frame(
+    /// The pattern is generated row by row while it is sent, without a frame buffer, and
+    /// shown with a full refresh using the current [RefreshLut]. See [TestPattern] for
+    /// the patterns.
+    ///
+    /// Does nothing while the display is asleep, see [is_sleeping](Epd4in2::is_sleeping)
+    pub fn display_test_pattern(
         &mut self,
         spi: &mut SPI,
-        buffer: &[u8],
         delay: &mut DELAY,
+        pattern: TestPattern,
     ) -> Result<(), SPI::Error> {
-        self.update_new_frame(spi, buffer, delay)?;
-        self.display_frame(spi, delay)
+        self.display_pixels(spi, delay, |x, y| pattern.is_white(x, y))
     }
 
-    fn update_partial_old_frame(
+    // sends the frame with the pixels set where `is_set` returns true and refreshes
+    fn display_pixels(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
-        buffer: &[u8],
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
+        is_set: impl Fn(u32, u32) -> bool,
     ) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
         self.wait_until_idle(spi, delay)?;
 
-        if buffer.len() as u32 != width / 8 * height {
-            //TODO: panic!! or sth like that
-            //return Err("Wrong buffersize");
+        if !self.skip_old_frame {
+            self.interface.cmd_with_data_x_times(
+                spi,
+                delay,
+                Command::DataStartTransmission1,
+                self.color.get_byte_value(),
+                self.buffer_size() as u32,
+            )?;
         }
 
-        self.interface.cmd(spi, Command::PartialIn)?;
-        self.interface.cmd(spi, Command::PartialWindow)?;
-
-        self.shift_display(spi, x, y, width, height)?;
-
-        self.interface.cmd(spi, Command::DataStartTransmission1)?;
-
-        self.interface.data(spi, buffer)?;
-
-        Ok(())
+        let dtm = Command::DataStartTransmission2;
+        self.interface
+            .frame_transaction(spi, dtm, |interface, spi| {
+                let mut row = [0u8; WIDTH as usize / 8];
+                for y in 0..HEIGHT {
+                    for (i, byte) in row.iter_mut().enumerate() {
+                        *byte = (0..8).fold(0, |byte, bit| {
+                            let x = i as u32 * 8 + bit;
+                            (byte << 1) | is_set(x, y) as u8
+                        });
+                    }
+                    interface.stream_paced(spi, delay, &row)?;
+                }
+                Ok(())
+            })?;
+        self.refresh(spi, delay)
     }
 
-    /// Always call `update_partial_old_frame` before this, with buffer-updating code
-    /// between the calls.
-    fn update_partial_new_frame(
+    /// Transmits a partial frame and refreshes the display, only ever adding black pixels
+    ///
+    /// Meant for accumulating content like pen strokes: Black pixels of `buffer` are
+    /// drawn, white pixels leave whatever is shown at their position untouched, so
+    /// there is no need to keep track of the old content.
+    ///
+    /// This is done by loading dedicated LUTs for the refresh: The transitions to white
+    /// (`WW` and `BW`) don't drive the pixels at all, the transitions to black (`WB` and
+    /// `BB`) use the single phase group of the fast refresh. Afterwards the LUTs of the
+    /// current [RefreshLut] are loaded again.
+    ///
+    /// BUFFER needs to cover the window returned by [aligned_window] for `x` and `width` !
+    ///
+    /// Does nothing while the display is asleep or if the region is empty, see
+    /// [is_sleeping](Epd4in2::is_sleeping)
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_and_display_partial_frame_accumulate(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -552,23 +897,32 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        if buffer.len() as u32 != width / 8 * height {
-            //TODO: panic!! or sth like that
-            //return Err("Wrong buffersize");
+        if self.sleeping || width == 0 || height == 0 {
+            return Ok(());
         }
+        self.set_lut_helper(spi, delay, &LUTS_ACCUMULATE)?;
 
-        self.shift_display(spi, x, y, width, height)?;
-
-        self.interface.cmd(spi, Command::DataStartTransmission2)?;
-
-        self.interface.data(spi, buffer)?;
+        self.update_partial_frame(spi, delay, buffer, x, y, width, height)?;
+        self.refresh(spi, delay)?;
 
-        self.interface.cmd(spi, Command::PartialOut)?;
-        Ok(())
+        self.set_lut(spi, delay, None)
     }
 
-    fn clear_partial_frame(
+    /// Fills a region with a color and refreshes only this region
+    ///
+    /// Meant for erasing a single widget before redrawing it: The region is written to
+    /// both frame buffers of the EPD and refreshed with the quick LUTs while in partial
+    /// mode, so the rest of the display doesn't flash. Afterwards the LUTs of the current
+    /// [RefreshLut] are loaded again.
+    ///
+    /// The controller only supports partial windows starting and ending on a byte
+    /// boundary. If `x` or `width` aren't multiples of 8 the region is widened to the
+    /// surrounding byte boundaries, so up to 7 pixels on each side are filled as well.
+    ///
+    /// Does nothing while the display is asleep or if the region is empty, see
+    /// [is_sleeping](Epd4in2::is_sleeping)
+    #[allow(clippy::too_many_arguments)]
+    pub fn clear_region(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -576,33 +930,847 @@ where
         y: u32,
         width: u32,
         height: u32,
+        color: Color,
     ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.send_resolution(spi)?;
-
-        let color_value = self.color.get_byte_value();
+        if self.sleeping || width == 0 || height == 0 {
+            return Ok(());
+        }
+        assert!(x + width <= WIDTH && y + height <= HEIGHT);
+        self.set_lut_helper(spi, delay, &LUTS_QUICK)?;
 
-        self.interface.cmd(spi, Command::PartialIn)?;
-        self.interface.cmd(spi, Command::PartialWindow)?;
+        let (start, window_width) = aligned_window(x, width);
+        let line_bytes = window_width / 8;
 
-        self.shift_display(spi, x, y, width, height)?;
+        self.command(spi, Command::PartialIn)?;
+        self.command(spi, Command::PartialWindow)?;
+        self.shift_display(spi, start, y, window_width, height)?;
 
-        self.interface.cmd(spi, Command::DataStartTransmission1)?;
-        self.interface
-            .data_x_times(spi, color_value, width / 8 * height)?;
+        for dtm in [
+            Command::DataStartTransmission1,
+            Command::DataStartTransmission2,
+        ] {
+            self.interface.cmd_with_data_x_times(
+                spi,
+                delay,
+                dtm,
+                color.get_byte_value(),
+                line_bytes * height,
+            )?;
+        }
 
-        self.interface.cmd(spi, Command::DataStartTransmission2)?;
-        self.interface
-            .data_x_times(spi, color_value, width / 8 * height)?;
+        self.refresh(spi, delay)?;
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialOut)?;
 
-        self.interface.cmd(spi, Command::PartialOut)?;
-        Ok(())
+        self.set_lut(spi, delay, None)
     }
-}
 
-#[cfg(test)]
+    /// Transmits a full width band of the display and refreshes only this band
+    ///
+    /// Meant for status lines, headers and footers: `buffer` contains the rows `y` up to
+    /// `y + height` over the whole width of the display. They are refreshed with the quick
+    /// LUTs while in partial mode, so the rest of the display doesn't flash. Afterwards the
+    /// LUTs of the current [RefreshLut] are loaded again.
+    ///
+    /// Does nothing while the display is asleep or if the region is empty, see
+    /// [is_sleeping](Epd4in2::is_sleeping)
+    pub fn display_band(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        y: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping || height == 0 {
+            return Ok(());
+        }
+        assert!(y + height <= HEIGHT);
+        self.set_lut_helper(spi, delay, &LUTS_QUICK)?;
+        self.display_window(spi, delay, buffer, 0, y, WIDTH, height)?;
+        self.set_lut(spi, delay, None)
+    }
+
+    /// Transmits a partial frame and refreshes only its window with the given LUTs
+    ///
+    /// Gives control over the waveform of a single update, e.g. with a custom fast LUT
+    /// tuned for a panel, without changing the LUTs of the other refreshes. The window
+    /// is refreshed in partial mode, so the rest of the display doesn't flash. The sizes
+    /// of the LUTs are guaranteed by [LutSet].
+    ///
+    /// If `restore` is set, the LUTs of the current [RefreshLut] are loaded again
+    /// afterwards, which waits until the refresh is finished. Otherwise `lut` stays
+    /// loaded, e.g. for several updates in a row.
+    ///
+    /// `buffer` covers the window returned by [aligned_window], like for
+    /// [update_partial_frame](WaveshareDisplay::update_partial_frame).
+    ///
+    /// Does nothing while the display is asleep or if the region is empty, see
+    /// [is_sleeping](Epd4in2::is_sleeping)
+    #[allow(clippy::too_many_arguments)]
+    pub fn display_partial_with_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        lut: &LutSet,
+        restore: bool,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping || width == 0 || height == 0 {
+            return Ok(());
+        }
+        assert!(x + width <= WIDTH && y + height <= HEIGHT);
+        self.set_lut_helper(spi, delay, lut)?;
+        self.display_window(spi, delay, buffer, x, y, width, height)?;
+        if restore {
+            self.set_lut(spi, delay, None)?;
+        }
+        Ok(())
+    }
+
+    // transmits a window in partial mode, refreshes it with the loaded LUTs and waits
+    // until the refresh is finished
+    #[allow(clippy::too_many_arguments)]
+    fn display_window(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == window_buffer_len(x, width, height));
+
+        self.command(spi, Command::PartialIn)?;
+        self.command(spi, Command::PartialWindow)?;
+        self.shift_display(spi, x, y, width, height)?;
+        self.interface
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission2, buffer)?;
+
+        self.refresh(spi, delay)?;
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PartialOut)
+    }
+
+    /// Sets the interval between the Vcom and data signals during a refresh
+    ///
+    /// This is the lower nibble (CDI) of the VcomAndDataIntervalSetting register, the
+    /// interval is `17 - data_interval` hsync periods:
+    ///
+    /// - `0x00`: 17 hsync
+    /// - `0x07`: 10 hsync (default)
+    /// - `0x0F`: 2 hsync
+    ///
+    /// Some panels show fewer artifacts with a different value. Only the lower 4 bits
+    /// of `data_interval` are used, the border (VBD) and data polarity (DDX) bits are
+    /// left untouched. The setting is sent right away and kept for the next `init`.
+    pub fn set_data_interval(
+        &mut self,
+        spi: &mut SPI,
+        data_interval: u8,
+    ) -> Result<(), SPI::Error> {
+        self.data_interval = data_interval & 0x0F;
+        self.send_vcom_and_data_interval(spi)
+    }
+
+    /// Sets how many times a failed SPI write is retried before its error is returned
+    ///
+    /// Helps with unreliable connections, like long cables, where a single write might
+    /// fail every now and then. The write is retried after the time set with
+    /// [set_spi_retry_delay_us](Epd4in2::set_spi_retry_delay_us). Defaults to 0, so
+    /// errors are returned immediately.
+    pub fn set_spi_retries(&mut self, retries: u8) {
+        self.interface.set_spi_retries(retries);
+    }
+
+    /// Sets the time to wait before a failed SPI write is retried, see
+    /// [set_spi_retries](Epd4in2::set_spi_retries)
+    ///
+    /// A glitch on a long cable takes a moment to settle, so retrying right away would
+    /// likely fail again. The delay is waited before retrying the writes of frame buffers
+    /// and of the fills of the display RAM, which make up nearly all the traffic.
+    /// Commands and their parameters are retried right away, as the driver can't wait
+    /// while sending them. Defaults to 0.
+    pub fn set_spi_retry_delay_us(&mut self, delay_us: u32) {
+        self.interface.set_spi_retry_delay_us(delay_us);
+    }
+
+    /// Sets a gap after every byte of a frame buffer sent to the display, in us
+    ///
+    /// For slow links, e.g. through opto-isolators smearing fast edges, which need some
+    /// time between the bytes. Frame buffers are then sent byte by byte with a delay
+    /// after each one, instead of in a single SPI transfer, while CS stays low for the
+    /// whole buffer. This is a lot slower: a
+    /// full frame of 15000 bytes takes at least 15000 times the delay longer, e.g. 150ms
+    /// more for 10us. Commands and their parameters are sent without gaps as usual.
+    /// Defaults to 0, which disables the gaps.
+    pub fn set_inter_byte_delay_us(&mut self, delay_us: u32) {
+        self.interface.set_inter_byte_delay_us(delay_us);
+    }
+
+    /// Takes everything sent to the display since the last call, see [testing](crate::testing)
+    #[cfg(feature = "testing")]
+    pub fn take_log(&mut self) -> alloc::vec::Vec<crate::testing::SpiEvent> {
+        self.interface.take_log()
+    }
+
+    /// Selects whether refreshes use the LUTs of the driver or the ones stored in the OTP
+    /// memory of the display
+    ///
+    /// This is the REG bit (bit 5) of the panel setting register. With [LutSource::Otp]
+    /// no LUTs are sent by [set_lut](WaveshareDisplay::set_lut) at all, which speeds up
+    /// `init` and uses the waveform tuned by the manufacturer. [RefreshLut] has no effect
+    /// then. The refreshes loading their own LUTs, like
+    /// [update_and_display_frame_fast](Epd4in2::update_and_display_frame_fast), need
+    /// [LutSource::Registers] to make a difference.
+    ///
+    /// Defaults to [LutSource::Registers]. The setting is sent right away and kept for
+    /// the next `init`.
+    pub fn set_lut_source(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut_source: LutSource,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.lut_source = lut_source;
+        self.send_panel_setting(spi)?;
+        self.set_lut(spi, delay, Some(self.refresh))
+    }
+
+    /// Inverts the whole output of the display in hardware
+    ///
+    /// Black pixels of the frame buffers are shown white and the other way around, which
+    /// allows e.g. a dark mode without touching the buffers. Takes effect with the next
+    /// refresh.
+    ///
+    /// The panel setting register has no invert bit on this controller, instead the data
+    /// polarity (DDX, bits 5:4) of the VcomAndDataIntervalSetting register is switched from
+    /// the default `01` to `00`. The setting is sent right away and kept for the next `init`.
+    pub fn set_inverted(&mut self, spi: &mut SPI, inverted: bool) -> Result<(), SPI::Error> {
+        self.inverted = inverted;
+        self.send_vcom_and_data_interval(spi)
+    }
+
+    /// Reverses the scan directions of the gate and source drivers in hardware
+    ///
+    /// `gate_rev` clears the UD bit (bit 3) of the panel setting register, the gates are
+    /// scanned down instead of up, which flips the image vertically. `source_rev` clears
+    /// the SHL bit (bit 2), the sources shift left instead of right, which flips it
+    /// horizontally. Both together turn the image by 180° without any cost at draw time.
+    ///
+    /// The frame buffers are sent unchanged, so the coordinates of partial windows refer
+    /// to the flipped image as well. The setting is sent right away and kept for the
+    /// next `init`, it takes effect with the next refresh.
+    pub fn set_scan_direction(
+        &mut self,
+        spi: &mut SPI,
+        gate_rev: bool,
+        source_rev: bool,
+    ) -> Result<(), SPI::Error> {
+        self.gate_reversed = gate_rev;
+        self.source_reversed = source_rev;
+        self.send_panel_setting(spi)
+    }
+
+    /// Sends a command stream captured with [recording](crate::recording)
+    ///
+    /// All the logic of the driver is skipped, the stream is sent as it was recorded.
+    /// As the driver doesn't know what was sent, the LUTs are loaded again before the next
+    /// refresh using [set_lut](WaveshareDisplay::set_lut).
+    ///
+    /// Returns the number of bytes of `stream` that were sent. Replaying stops at the first
+    /// malformed or truncated operation, so a result below `stream.len()` means the stream
+    /// was only partly sent.
+    pub fn replay(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        stream: &[u8],
+    ) -> Result<usize, SPI::Error> {
+        self.lut = None;
+        self.interface.replay(spi, delay, IS_BUSY_LOW, stream)
+    }
+
+    /// Leaves the display in a known state before the program ends
+    ///
+    /// If `clear` is set, the display is first cleared to the background color, so no
+    /// frame is left on it for a long time. Then it is put into deep sleep, which keeps
+    /// the image and needs no power. Call this before dropping the driver, it can't be
+    /// done on drop as it needs the SPI and can fail.
+    pub fn shutdown(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        clear: bool,
+    ) -> Result<(), SPI::Error> {
+        if clear && !self.sleeping {
+            self.clear_frame(spi, delay)?;
+            self.display_frame(spi, delay)?;
+        }
+        self.sleep(spi, delay)
+    }
+
+    /// Returns true while the display is in deep sleep
+    ///
+    /// After [sleep](WaveshareDisplay::sleep) the display ignores everything sent until it
+    /// was woken up with [wake_up](WaveshareDisplay::wake_up). Therefore all the functions
+    /// transmitting frames or refreshing the display return `Ok` without sending anything
+    /// while it sleeps, check this before relying on a frame being shown.
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Returns how long the display was busy during the last refresh, in ms
+    ///
+    /// The time is approximated by counting the delays between the checks of the busy
+    /// pin while waiting for the refresh to finish, so it is a multiple of the delay given
+    /// to [new](WaveshareDisplay::new) (10ms by default) and always 0 with a delay of 0.
+    /// It is taken once the driver waited for the refresh, e.g. with the next command,
+    /// and allows comparing the durations of the different LUTs.
+    pub fn last_refresh_ms(&self) -> u16 {
+        (self.last_refresh_us / 1000).min(u16::MAX as u32) as u16
+    }
+
+    /// Skips filling the old frame (DTM1) in [update_frame](WaveshareDisplay::update_frame)
+    ///
+    /// `update_frame` fills the old frame with the background color before transmitting
+    /// the new one, which doubles the data sent. The LUTs of both [RefreshLut]s drive
+    /// every pixel only by its new color (white to white is the same as black to white
+    /// and so on), so the old frame makes no difference with them and can be skipped.
+    ///
+    /// Only safe with [LutSource::Registers]: The LUTs in the OTP might depend on the old
+    /// frame, which then contains whatever was transmitted last. Defaults to false.
+    pub fn set_skip_old_frame(&mut self, skip_old_frame: bool) {
+        self.skip_old_frame = skip_old_frame;
+    }
+
+    /// Clears the display to the background color at the end of every initialization
+    ///
+    /// The memory of the display contains random data after powering it up, which shows
+    /// up as noise with the first partial refresh. With this set, `init` and
+    /// [wake_up](WaveshareDisplay::wake_up) clear both frame buffers and start a refresh,
+    /// which takes as long as a full refresh, a few seconds with the default LUT.
+    ///
+    /// Defaults to false. To clear already during [new](WaveshareDisplay::new), create the
+    /// driver with [new_uninitialized](Epd4in2::new_uninitialized) and call `wake_up`
+    /// after setting this.
+    pub fn set_clear_on_init(&mut self, clear_on_init: bool) {
+        self.clear_on_init = clear_on_init;
+    }
+
+    /// Sets an additional time to wait after a refresh has finished
+    ///
+    /// Refreshes are synchronised through the busy pin, so this defaults to 0 and is
+    /// only needed for boards that need some extra time to settle after the busy pin
+    /// was released. If set, [display_frame](WaveshareDisplay::display_frame) and
+    /// [update_and_display_frame](WaveshareDisplay::update_and_display_frame) block until
+    /// the refresh is finished and the settle time has passed.
+    pub fn set_refresh_settle_us(&mut self, settle_us: u32) {
+        self.refresh_settle_us = settle_us;
+    }
+
+    /// Sets how long to wait for the busy pin to assert after starting a refresh
+    ///
+    /// Some revisions only assert the busy pin a while after the refresh command, so
+    /// waiting for the display to be idle right after it returns immediately and the
+    /// next commands are sent during the refresh. With a confirm window, the driver makes
+    /// sure the display is idle before starting a refresh and then waits up to
+    /// `confirm_us` for the busy pin to assert, before it waits for it to be released
+    /// as usual. Defaults to 0, which disables both waits.
+    pub fn set_busy_confirm_us(&mut self, confirm_us: u32) {
+        self.busy_confirm_us = confirm_us;
+    }
+
+    /// Non-blocking check whether the display has stopped processing data
+    ///
+    /// Returns `WouldBlock` as long as the busy pin is asserted and `Ok(())` once the
+    /// display is idle. Errors while reading the busy pin are passed on.
+    ///
+    /// This doesn't delay at all, so it can be polled from a main loop in between other
+    /// work, or be turned into the blocking [wait_until_idle](WaveshareDisplay::wait_until_idle)
+    /// with `nb::block!`.
+    pub fn try_wait(&mut self) -> nb::Result<(), BUSY::Error> {
+        if self.interface.try_is_busy(IS_BUSY_LOW)? {
+            Err(nb::Error::WouldBlock)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resets the device and waits until it signals readiness on the busy pin
+    ///
+    /// Instead of the fixed 200ms delay used by the normal reset, this waits at most
+    /// `timeout_us` for busy to be asserted and released again. If busy doesn't toggle
+    /// within that time, the fixed delay is used as a fallback.
+    ///
+    /// Returns `true` if the panel signalled its readiness through the busy pin.
+    pub fn reset_and_wait(&mut self, delay: &mut DELAY, timeout_us: u32) -> bool {
+        self.interface
+            .reset_and_wait(delay, 10_000, 10_000, IS_BUSY_LOW, timeout_us)
+    }
+
+    /// Checks whether a display is connected and responding
+    ///
+    /// Resets the display and powers it on, which asserts the busy pin while the
+    /// booster starts. If busy isn't asserted and released again within `timeout_us`,
+    /// the display is most likely missing or miswired and `false` is returned. This
+    /// doesn't hang like [wait_until_idle](WaveshareDisplay::wait_until_idle) would, so it
+    /// can be used at startup to report a missing display.
+    ///
+    /// Afterwards the display is initialised again, so it can be used right away. If the
+    /// check failed, the display is left as it is.
+    pub fn check_connection(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        timeout_us: u32,
+    ) -> Result<bool, SPI::Error> {
+        self.reset_and_wait(delay, timeout_us);
+
+        self.interface.send_command_with_data(
+            spi,
+            Command::PowerSetting,
+            &[0x03, 0x00, 0x2b, 0x2b, 0xff],
+        )?;
+        self.interface.send_command_with_data(
+            spi,
+            Command::BoosterSoftStart,
+            &[0x17, 0x17, 0x17],
+        )?;
+        self.command(spi, Command::PowerOn)?;
+
+        let toggle = self
+            .interface
+            .wait_busy_toggle(delay, IS_BUSY_LOW, timeout_us);
+        if toggle != BusyToggle::Toggled {
+            return Ok(false);
+        }
+
+        self.init(spi, delay)?;
+        Ok(true)
+    }
+
+    /// Turns off the booster and source/gate drivers without entering deep sleep
+    ///
+    /// This is a lighter alternative to [sleep](WaveshareDisplay::sleep): the controller
+    /// stays powered, so register settings, loaded LUTs and the frame SRAM are kept.
+    /// Resuming only requires [power_on](Epd4in2::power_on), no reset or `init`.
+    ///
+    /// [sleep](WaveshareDisplay::sleep) draws less current, but the deep sleep it
+    /// enters can only be left with a hardware reset followed by a full `init`,
+    /// which [wake_up](WaveshareDisplay::wake_up) takes care of.
+    pub fn power_off(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::PowerOff)?;
+        self.wait_until_idle(spi, delay)
+    }
+
+    /// Turns the booster and drivers back on after [power_off](Epd4in2::power_off)
+    pub fn power_on(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.command(spi, Command::PowerOn)?;
+        delay.delay_us(5000);
+        self.wait_until_idle(spi, delay)
+    }
+
+    /// Helper function. Sets up the display to send pixel data to a custom
+    /// starting point.
+    ///
+    /// The window is widened to whole bytes, it covers the window returned by
+    /// [aligned_window] for `x` and `width`. Panics if `width` or `height` is 0, as
+    /// there is no empty window.
+    pub fn shift_display(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        assert!(width > 0 && height > 0);
+        // the controller ignores the lowest 3 bits of the horizontal borders
+        let (start, window_width) = aligned_window(x, width);
+        let end = start + window_width - 1;
+        self.send_data(spi, &[(start >> 8) as u8])?;
+        self.send_data(spi, &[start as u8])?;
+        self.send_data(spi, &[(end >> 8) as u8])?;
+        self.send_data(spi, &[end as u8])?;
+
+        self.send_data(spi, &[(y >> 8) as u8])?;
+        self.send_data(spi, &[y as u8])?;
+
+        self.send_data(spi, &[((y + height - 1) >> 8) as u8])?;
+        self.send_data(spi, &[(y + height - 1) as u8])?;
+
+        self.send_data(spi, &[0x01])?; // Gates scan both inside and outside of the partial window. (default)
+
+        Ok(())
+    }
+
+    /// Transmits a region of a full frame buffer to the SRAM of the EPD
+    ///
+    /// Same as [update_partial_frame](WaveshareDisplay::update_partial_frame), but the
+    /// rows are taken directly out of a full `WIDTH x HEIGHT` buffer, so there is no
+    /// need to copy the region into a tightly packed buffer first.
+    ///
+    /// The controller only supports partial windows starting and ending on a byte
+    /// boundary. If `x` or `width` aren't multiples of 8 the window is widened to the
+    /// surrounding byte boundaries. As the pixels are taken from the full buffer,
+    /// the additional pixels are sent with their correct values.
+    ///
+    /// BUFFER needs to be of size: WIDTH / 8 * HEIGHT !
+    ///
+    /// Does nothing while the display is asleep or if the region is empty, see
+    /// [is_sleeping](Epd4in2::is_sleeping)
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_partial_frame_from_full(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping || width == 0 || height == 0 {
+            return Ok(());
+        }
+        assert!(buffer.len() == self.buffer_size());
+        assert!(x + width <= WIDTH && y + height <= HEIGHT);
+        self.wait_until_idle(spi, delay)?;
+
+        let line_bytes = WIDTH as usize / 8;
+        let start = x as usize / 8;
+        let end = (x + width).div_ceil(8) as usize;
+
+        self.command(spi, Command::PartialIn)?;
+        self.command(spi, Command::PartialWindow)?;
+        self.shift_display(spi, x, y, width, height)?;
+
+        let dtm = Command::DataStartTransmission2;
+        self.interface
+            .frame_transaction(spi, dtm, |interface, spi| {
+                for row in buffer
+                    .chunks(line_bytes)
+                    .skip(y as usize)
+                    .take(height as usize)
+                {
+                    interface.stream_paced(spi, delay, &row[start..end])?;
+                }
+                Ok(())
+            })?;
+
+        self.command(spi, Command::PartialOut)?;
+        Ok(())
+    }
+}
+
+impl<SPI, CS, BUSY, DC, RST, DELAY> QuickRefresh<SPI, CS, BUSY, DC, RST, DELAY>
+    for Epd4in2<SPI, CS, BUSY, DC, RST, DELAY>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayUs<u32>,
+{
+    /// To be followed immediately after by `update_old_frame`.
+    fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
+        self.wait_until_idle(spi, delay)?;
+
+        self.interface
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission1, buffer)?;
+
+        Ok(())
+    }
+
+    /// To be used immediately after `update_old_frame`.
+    fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping {
+            return Ok(());
+        }
+        self.wait_until_idle(spi, delay)?;
+        // self.send_resolution(spi)?;
+
+        self.interface
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission2, buffer)?;
+
+        Ok(())
+    }
+
+    /// This is a wrapper around `display_frame` for using this device as a true
+    /// `QuickRefresh` device.
+    fn display_new_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+        self.display_frame(spi, delay)
+    }
+
+    /// This is wrapper around `update_new_frame` and `display_frame` for using
+    /// this device as a true `QuickRefresh` device.
+    ///
+    /// To be used immediately after `update_old_frame`.
+    fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), SPI::Error> {
+        self.update_new_frame(spi, buffer, delay)?;
+        self.display_frame(spi, delay)
+    }
+
+    fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping || width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.wait_until_idle(spi, delay)?;
+
+        assert!(buffer.len() == window_buffer_len(x, width, height));
+
+        self.interface.cmd(spi, Command::PartialIn)?;
+        self.interface.cmd(spi, Command::PartialWindow)?;
+
+        self.shift_display(spi, x, y, width, height)?;
+
+        self.interface
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission1, buffer)?;
+
+        Ok(())
+    }
+
+    /// Always call `update_partial_old_frame` before this, with buffer-updating code
+    /// between the calls.
+    fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping || width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.wait_until_idle(spi, delay)?;
+        assert!(buffer.len() == window_buffer_len(x, width, height));
+
+        self.shift_display(spi, x, y, width, height)?;
+
+        self.interface
+            .cmd_with_buffer(spi, delay, Command::DataStartTransmission2, buffer)?;
+
+        self.interface.cmd(spi, Command::PartialOut)?;
+        Ok(())
+    }
+
+    fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        if self.sleeping || width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.wait_until_idle(spi, delay)?;
+        self.send_resolution(spi)?;
+
+        let color_value = self.color.get_byte_value();
+
+        self.interface.cmd(spi, Command::PartialIn)?;
+        self.interface.cmd(spi, Command::PartialWindow)?;
+
+        self.shift_display(spi, x, y, width, height)?;
+
+        self.interface.cmd_with_data_x_times(
+            spi,
+            delay,
+            Command::DataStartTransmission1,
+            color_value,
+            window_buffer_len(x, width, height) as u32,
+        )?;
+
+        self.interface.cmd_with_data_x_times(
+            spi,
+            delay,
+            Command::DataStartTransmission2,
+            color_value,
+            window_buffer_len(x, width, height) as u32,
+        )?;
+
+        self.interface.cmd(spi, Command::PartialOut)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
+    extern crate std;
+    use core::{
+        cell::{Cell, RefCell},
+        convert::Infallible,
+    };
+    use std::{rc::Rc, vec::Vec};
+
+    /// Records every byte sent over SPI together with the state of the DC pin
+    #[derive(Default)]
+    struct Bus {
+        dc_high: bool,
+        /// number of the following writes failing
+        failures: u32,
+        /// number of the following data writes failing
+        data_failures: u32,
+        /// number of times CS was released
+        transactions: u32,
+        /// (is command, byte)
+        sent: Vec<(bool, u8)>,
+    }
+
+    struct Spi(Rc<RefCell<Bus>>);
+
+    impl Write<u8> for Spi {
+        type Error = ();
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            let mut bus = self.0.borrow_mut();
+            if bus.failures > 0 {
+                bus.failures -= 1;
+                return Err(());
+            }
+            if bus.dc_high && bus.data_failures > 0 {
+                bus.data_failures -= 1;
+                return Err(());
+            }
+            let is_command = !bus.dc_high;
+            bus.sent
+                .extend(words.iter().map(|&word| (is_command, word)));
+            Ok(())
+        }
+    }
+
+    struct Cs(Rc<RefCell<Bus>>);
+
+    impl OutputPin for Cs {
+        type Error = Infallible;
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().transactions += 1;
+            Ok(())
+        }
+    }
+
+    struct Dc(Rc<RefCell<Bus>>);
+
+    impl OutputPin for Dc {
+        type Error = Infallible;
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().dc_high = false;
+            Ok(())
+        }
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().dc_high = true;
+            Ok(())
+        }
+    }
+
+    /// Output pin doing nothing, as input pin it is never busy
+    struct Pin;
+
+    impl OutputPin for Pin {
+        type Error = Infallible;
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl InputPin for Pin {
+        type Error = Infallible;
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(false)
+        }
+    }
+
+    struct Delay;
+
+    impl DelayUs<u32> for Delay {
+        fn delay_us(&mut self, _us: u32) {}
+    }
+
+    type TestEpd = Epd4in2<Spi, Pin, Pin, Dc, Pin, Delay>;
+
+    /// Creates an initialised driver and clears the recorded bytes of the init sequence
+    fn epd() -> (TestEpd, Spi, Rc<RefCell<Bus>>) {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let mut spi = Spi(bus.clone());
+        let epd = Epd4in2::new(&mut spi, Pin, Pin, Dc(bus.clone()), Pin, &mut Delay, None).unwrap();
+        bus.borrow_mut().sent.clear();
+        (epd, spi, bus)
+    }
+
+    /// All the commands sent since the last clear
+    fn commands(bus: &Rc<RefCell<Bus>>) -> Vec<u8> {
+        let bus = bus.borrow();
+        bus.sent
+            .iter()
+            .filter(|(is_command, _)| *is_command)
+            .map(|(_, byte)| *byte)
+            .collect()
+    }
+
+    /// The data sent right after the first occurence of `command` since the last clear
+    fn data_of(bus: &Rc<RefCell<Bus>>, command: Command) -> Vec<u8> {
+        use crate::traits::Command as _;
+        let bus = bus.borrow();
+        bus.sent
+            .iter()
+            .skip_while(|&&(is_command, byte)| !(is_command && byte == command.address()))
+            .skip(1)
+            .take_while(|(is_command, _)| !*is_command)
+            .map(|(_, byte)| *byte)
+            .collect()
+    }
 
     #[test]
     fn epd_size() {
@@ -610,4 +1778,791 @@ mod tests {
         assert_eq!(HEIGHT, 300);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[test]
+    fn lut_only_sent_when_changed() {
+        let (mut epd, mut spi, bus) = epd();
+        let luts = [0x20, 0x21, 0x22, 0x23, 0x24];
+
+        epd.set_lut(&mut spi, &mut Delay, Some(RefreshLut::Quick))
+            .unwrap();
+        assert_eq!(commands(&bus), luts);
+        assert_eq!(data_of(&bus, Command::LutForVcom), LUT_VCOM0_QUICK);
+        assert_eq!(epd.current_lut(), Some(&LUTS_QUICK));
+
+        bus.borrow_mut().sent.clear();
+        epd.set_lut(&mut spi, &mut Delay, Some(RefreshLut::Quick))
+            .unwrap();
+        assert_eq!(commands(&bus), []);
+
+        // forced reload
+        epd.set_lut(&mut spi, &mut Delay, None).unwrap();
+        assert_eq!(commands(&bus), luts);
+    }
+
+    #[test]
+    fn power_off_keeps_controller_awake() {
+        let (mut epd, mut spi, bus) = epd();
+
+        epd.power_off(&mut spi, &mut Delay).unwrap();
+        assert_eq!(commands(&bus), [0x02]);
+
+        bus.borrow_mut().sent.clear();
+        epd.power_on(&mut spi, &mut Delay).unwrap();
+        assert_eq!(commands(&bus), [0x04]);
+    }
+
+    #[test]
+    fn check_connection_without_busy_toggle() {
+        let (mut epd, mut spi, bus) = epd();
+
+        // the test busy pin never gets busy, like a missing display
+        assert!(!epd.check_connection(&mut spi, &mut Delay, 10_000).unwrap());
+        // no init after a failed check
+        assert_eq!(commands(&bus), [0x01, 0x06, 0x04]);
+    }
+
+    #[test]
+    fn accumulate_sequence() {
+        let (mut epd, mut spi, bus) = epd();
+        let buffer = [0u8; 2 * 16];
+
+        epd.update_and_display_partial_frame_accumulate(
+            &mut spi, &mut Delay, &buffer, 8, 8, 16, 16,
+        )
+        .unwrap();
+
+        let luts = [0x20, 0x21, 0x22, 0x23, 0x24];
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&luts);
+        expected.extend_from_slice(&[0x91, 0x90, 0x13, 0x92, 0x12]);
+        expected.extend_from_slice(&luts);
+        assert_eq!(commands(&bus), expected);
+
+        assert_eq!(data_of(&bus, Command::LutWhiteToWhite), LUT_KEEP);
+        assert_eq!(data_of(&bus, Command::LutBlackToWhite), LUT_KEEP);
+        assert_eq!(data_of(&bus, Command::LutWhiteToBlack), LUT_WB_FAST);
+    }
+
+    #[test]
+    fn vcom_and_data_interval() {
+        let (mut epd, mut spi, bus) = epd();
+
+        epd.set_data_interval(&mut spi, 0xF3).unwrap();
+        assert_eq!(data_of(&bus, Command::VcomAndDataIntervalSetting), [0x93]);
+
+        bus.borrow_mut().sent.clear();
+        epd.init(&mut spi, &mut Delay).unwrap();
+        assert_eq!(data_of(&bus, Command::VcomAndDataIntervalSetting), [0x93]);
+
+        bus.borrow_mut().sent.clear();
+        epd.set_inverted(&mut spi, true).unwrap();
+        assert_eq!(data_of(&bus, Command::VcomAndDataIntervalSetting), [0x83]);
+    }
+
+    #[test]
+    fn scan_direction() {
+        let (mut epd, mut spi, bus) = epd();
+
+        epd.set_scan_direction(&mut spi, true, false).unwrap();
+        assert_eq!(data_of(&bus, Command::PanelSetting), [0x37]);
+
+        bus.borrow_mut().sent.clear();
+        epd.set_scan_direction(&mut spi, true, true).unwrap();
+        assert_eq!(data_of(&bus, Command::PanelSetting), [0x33]);
+
+        bus.borrow_mut().sent.clear();
+        epd.init(&mut spi, &mut Delay).unwrap();
+        assert_eq!(data_of(&bus, Command::PanelSetting), [0x33]);
+
+        bus.borrow_mut().sent.clear();
+        epd.set_scan_direction(&mut spi, false, false).unwrap();
+        assert_eq!(data_of(&bus, Command::PanelSetting), [0x3F]);
+    }
+
+    #[test]
+    fn display_frames() {
+        let (mut epd, mut spi, bus) = epd();
+        let old = [0x00; WIDTH as usize / 8 * HEIGHT as usize];
+        let new = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+
+        epd.display_frames(&mut spi, &old, &new, &mut Delay)
+            .unwrap();
+        assert_eq!(commands(&bus), [0x10, 0x13, 0x12]);
+        assert!(data_of(&bus, Command::DataStartTransmission1) == old);
+        assert!(data_of(&bus, Command::DataStartTransmission2) == new);
+    }
+
+    #[test]
+    #[should_panic]
+    fn update_frame_wrong_size() {
+        let (mut epd, mut spi, _bus) = epd();
+        let frame = [0xFF; WIDTH as usize / 8 * HEIGHT as usize - 1];
+
+        let _ = epd.update_frame(&mut spi, &frame, &mut Delay);
+    }
+
+    #[test]
+    #[should_panic]
+    fn display_frames_wrong_size() {
+        let (mut epd, mut spi, _bus) = epd();
+        let new = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+
+        let _ = epd.display_frames(&mut spi, &new[1..], &new, &mut Delay);
+    }
+
+    #[test]
+    fn display_transposed() {
+        let (mut epd, mut spi, bus) = epd();
+        let stride = HEIGHT as usize / 8 + 1;
+        let mut buffer = [0xFF; (HEIGHT as usize / 8 + 1) * WIDTH as usize];
+        // black pixels at the top left and bottom right of the portrait buffer
+        buffer[0] = 0x7F;
+        buffer[stride * WIDTH as usize - 1] = 0xEF;
+
+        epd.display_transposed(&mut spi, &mut Delay, &buffer, HEIGHT, WIDTH)
+            .unwrap();
+        assert_eq!(commands(&bus), [0x10, 0x13, 0x12]);
+        let frame = data_of(&bus, Command::DataStartTransmission2);
+        assert_eq!(frame.len(), epd.buffer_size());
+        // on the panel, they are at the top right and the bottom left
+        assert_eq!(frame[WIDTH as usize / 8 - 1], 0xFE);
+        assert_eq!(frame[epd.buffer_size() - WIDTH as usize / 8], 0x7F);
+        assert_eq!(frame.iter().filter(|&&byte| byte != 0xFF).count(), 2);
+    }
+
+    /// Delay summing up the time waited
+    #[derive(Default)]
+    struct DelaySum(u32);
+
+    impl DelayUs<u32> for DelaySum {
+        fn delay_us(&mut self, us: u32) {
+            self.0 = self.0.saturating_add(us);
+        }
+    }
+
+    #[test]
+    fn reset_and_wait_fallback() {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let busy_reads = Rc::new(Cell::new(0));
+        let mut spi = Spi(bus.clone());
+        let busy = BusyFor(busy_reads.clone());
+        let mut delay = DelaySum::default();
+        let mut epd = Epd4in2::new(&mut spi, Pin, busy, Dc(bus), Pin, &mut delay, None).unwrap();
+
+        // busy toggles: no fixed delay after the 10ms reset pulse
+        busy_reads.set(3);
+        delay.0 = 0;
+        assert!(epd.reset_and_wait(&mut delay, 50_000));
+        assert_eq!(delay.0, 10_000 + 10_000 + 3_000);
+
+        // busy never released: the rest of the 200ms is waited after the timeout
+        busy_reads.set(u32::MAX);
+        delay.0 = 0;
+        assert!(!epd.reset_and_wait(&mut delay, 50_000));
+        assert_eq!(delay.0, 10_000 + 10_000 + 200_000);
+
+        // busy never asserted
+        busy_reads.set(0);
+        delay.0 = 0;
+        assert!(!epd.reset_and_wait(&mut delay, 50_000));
+        assert_eq!(delay.0, 10_000 + 10_000 + 200_000);
+
+        // the polled time doesn't overflow close to the largest timeout
+        busy_reads.set(u32::MAX);
+        assert!(!epd.reset_and_wait(&mut delay, u32::MAX - 500));
+    }
+
+    #[test]
+    fn inter_byte_delay() {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let mut spi = Spi(bus.clone());
+        let mut delay = DelaySum::default();
+        let (cs, dc) = (Cs(bus.clone()), Dc(bus.clone()));
+        let mut epd = Epd4in2::new(&mut spi, cs, Pin, dc, Pin, &mut delay, None).unwrap();
+        let old = [0x00; WIDTH as usize / 8 * HEIGHT as usize];
+        let new = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+
+        delay.0 = 0;
+        epd.display_frames(&mut spi, &old, &new, &mut delay)
+            .unwrap();
+        assert_eq!(delay.0, 0);
+
+        // a gap after every byte, still within a single transaction per frame buffer
+        epd.set_inter_byte_delay_us(10);
+        bus.borrow_mut().sent.clear();
+        bus.borrow_mut().transactions = 0;
+        epd.display_frames(&mut spi, &old, &new, &mut delay)
+            .unwrap();
+        assert_eq!(delay.0, 10 * 2 * old.len() as u32);
+        assert_eq!(bus.borrow().transactions, 3);
+        assert!(data_of(&bus, Command::DataStartTransmission1) == old);
+        assert!(data_of(&bus, Command::DataStartTransmission2) == new);
+
+        // also for the rows of a region of a full buffer, 8 bytes each
+        delay.0 = 0;
+        epd.update_partial_frame_from_full(&mut spi, &mut delay, &new, 0, 0, 64, 2)
+            .unwrap();
+        assert_eq!(delay.0, 10 * 2 * 8);
+    }
+
+    #[test]
+    fn frame_transaction() {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let mut spi = Spi(bus.clone());
+        let (cs, dc) = (Cs(bus.clone()), Dc(bus.clone()));
+        let mut epd = Epd4in2::new(&mut spi, cs, Pin, dc, Pin, &mut Delay, None).unwrap();
+
+        // resolution and both frame buffers, CS is held low for each of them
+        bus.borrow_mut().sent.clear();
+        bus.borrow_mut().transactions = 0;
+        epd.clear_frame(&mut spi, &mut Delay).unwrap();
+        assert_eq!(bus.borrow().transactions, 3);
+        assert_eq!(commands(&bus), [0x61, 0x10, 0x13]);
+        assert_eq!(
+            data_of(&bus, Command::DataStartTransmission2).len(),
+            epd.buffer_size()
+        );
+
+        bus.borrow_mut().transactions = 0;
+        epd.display_test_pattern(&mut spi, &mut Delay, TestPattern::AllWhite)
+            .unwrap();
+        assert_eq!(bus.borrow().transactions, 3);
+
+        // the rows of a region are sent in a single transaction, whatever their number
+        let full = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+        let mut transactions = [0; 2];
+        for (height, count) in [1, 16].into_iter().zip(&mut transactions) {
+            bus.borrow_mut().transactions = 0;
+            epd.update_partial_frame_from_full(&mut spi, &mut Delay, &full, 8, 0, 64, height)
+                .unwrap();
+            *count = bus.borrow().transactions;
+        }
+        assert_eq!(transactions[0], transactions[1]);
+    }
+
+    #[test]
+    fn display_test_pattern() {
+        let (mut epd, mut spi, bus) = epd();
+        let row = WIDTH as usize / 8;
+
+        epd.display_test_pattern(&mut spi, &mut Delay, TestPattern::Checkerboard)
+            .unwrap();
+        assert_eq!(commands(&bus), [0x10, 0x13, 0x12]);
+        let frame = data_of(&bus, Command::DataStartTransmission2);
+        assert_eq!(frame.len(), epd.buffer_size());
+        assert_eq!(frame[..2], [0x00, 0xFF]);
+        assert_eq!(frame[8 * row..8 * row + 2], [0xFF, 0x00]);
+
+        bus.borrow_mut().sent.clear();
+        epd.display_test_pattern(&mut spi, &mut Delay, TestPattern::Border)
+            .unwrap();
+        let frame = data_of(&bus, Command::DataStartTransmission2);
+        assert_eq!(frame[40 * row..41 * row].first(), Some(&0x0F));
+        assert_eq!(frame[40 * row..41 * row].last(), Some(&0xF0));
+        // the corner square
+        assert_eq!(frame[10 * row + 3..10 * row + 5], [0x00, 0xFF]);
+
+        bus.borrow_mut().sent.clear();
+        epd.display_test_pattern(&mut spi, &mut Delay, TestPattern::Gradient)
+            .unwrap();
+        let frame = data_of(&bus, Command::DataStartTransmission2);
+        assert_eq!((frame[0], frame[row - 1]), (0x00, 0xFF));
+    }
+
+    #[test]
+    fn update_partial_frame_from_full() {
+        let (mut epd, mut spi, bus) = epd();
+        let mut full = [0; WIDTH as usize / 8 * HEIGHT as usize];
+        for (i, byte) in full.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        // pixels 5 to 12 of rows 3 and 4 are in the first two bytes of the rows
+        epd.update_partial_frame_from_full(&mut spi, &mut Delay, &full, 5, 3, 8, 2)
+            .unwrap();
+        assert_eq!(commands(&bus), [0x91, 0x90, 0x13, 0x92]);
+        assert_eq!(
+            data_of(&bus, Command::PartialWindow),
+            [0x00, 0x00, 0x00, 0x0F, 0x00, 0x03, 0x00, 0x04, 0x01]
+        );
+        assert_eq!(
+            data_of(&bus, Command::DataStartTransmission2),
+            [150, 151, 200, 201]
+        );
+    }
+
+    #[test]
+    fn empty_regions() {
+        let (mut epd, mut spi, bus) = epd();
+        let full = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+
+        epd.update_partial_frame(&mut spi, &mut Delay, &[], 0, 0, 0, 8)
+            .unwrap();
+        epd.update_partial_frame_from_full(&mut spi, &mut Delay, &full, 8, 8, 16, 0)
+            .unwrap();
+        epd.clear_region(&mut spi, &mut Delay, 8, 8, 0, 0, Color::Black)
+            .unwrap();
+        epd.display_band(&mut spi, &mut Delay, &[], 10, 0).unwrap();
+        assert!(bus.borrow().sent.is_empty());
+    }
+
+    #[test]
+    fn aligned_partial_window() {
+        assert_eq!(aligned_window(0, 64), (0, 64));
+        assert_eq!(aligned_window(5, 8), (0, 16));
+        assert_eq!(aligned_window(260, 1), (256, 8));
+        assert_eq!(aligned_window(13, 0), (8, 0));
+
+        // the end of the window keeps the high bits of x
+        let (mut epd, mut spi, bus) = epd();
+        let (x, width) = aligned_window(264, 16);
+        epd.update_partial_frame(&mut spi, &mut Delay, &[0xFF; 4], x, 0, width, 2)
+            .unwrap();
+        assert_eq!(
+            data_of(&bus, Command::PartialWindow),
+            [0x01, 0x08, 0x01, 0x17, 0x00, 0x00, 0x00, 0x01, 0x01]
+        );
+
+        // an unaligned window is widened to the one returned by aligned_window, its
+        // buffer is 16 pixels wide
+        bus.borrow_mut().sent.clear();
+        assert_eq!(aligned_window(5, 8), (0, 16));
+        epd.update_partial_frame(&mut spi, &mut Delay, &[0xFF; 4], 5, 0, 8, 2)
+            .unwrap();
+        assert_eq!(
+            data_of(&bus, Command::PartialWindow),
+            [0x00, 0x00, 0x00, 0x0F, 0x00, 0x00, 0x00, 0x01, 0x01]
+        );
+        assert_eq!(data_of(&bus, Command::DataStartTransmission2), [0xFF; 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn unaligned_partial_window_buffer() {
+        let (mut epd, mut spi, _bus) = epd();
+        // sized for the 8 pixels of the region instead of the 16 of its window
+        let _ = epd.update_partial_frame(&mut spi, &mut Delay, &[0xFF; 2], 5, 0, 8, 2);
+    }
+
+    /// Input pin being busy (low) for the given number of reads
+    struct BusyFor(Rc<Cell<u32>>);
+
+    impl InputPin for BusyFor {
+        type Error = Infallible;
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            self.is_low().map(|low| !low)
+        }
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            let reads = self.0.get();
+            self.0.set(reads.saturating_sub(1));
+            Ok(reads > 0)
+        }
+    }
+
+    #[test]
+    fn last_refresh_ms() {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let busy_reads = Rc::new(Cell::new(0));
+        let mut spi = Spi(bus.clone());
+        let busy = BusyFor(busy_reads.clone());
+        let mut epd =
+            Epd4in2::new(&mut spi, Pin, busy, Dc(bus), Pin, &mut Delay, Some(5_000)).unwrap();
+        assert_eq!(epd.last_refresh_ms(), 0);
+
+        // busy before the refresh isn't counted
+        busy_reads.set(3);
+        epd.wait_until_idle(&mut spi, &mut Delay).unwrap();
+        epd.display_frame(&mut spi, &mut Delay).unwrap();
+        busy_reads.set(80);
+        epd.wait_until_idle(&mut spi, &mut Delay).unwrap();
+        assert_eq!(epd.last_refresh_ms(), 400);
+
+        // nor after it
+        busy_reads.set(2);
+        epd.wait_until_idle(&mut spi, &mut Delay).unwrap();
+        assert_eq!(epd.last_refresh_ms(), 400);
+    }
+
+    #[test]
+    fn busy_confirm() {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let busy_reads = Rc::new(Cell::new(0));
+        let mut spi = Spi(bus.clone());
+        let busy = BusyFor(busy_reads.clone());
+        let mut epd =
+            Epd4in2::new(&mut spi, Pin, busy, Dc(bus), Pin, &mut Delay, Some(5_000)).unwrap();
+        epd.set_busy_confirm_us(20_000);
+
+        // busy never asserts: the whole window is waited for
+        epd.display_frame(&mut spi, &mut Delay).unwrap();
+        epd.wait_until_idle(&mut spi, &mut Delay).unwrap();
+        assert_eq!(epd.last_refresh_ms(), 20);
+
+        // also for the refreshes waited for within a method
+        epd.set_busy_confirm_us(10_000);
+        epd.clear_region(&mut spi, &mut Delay, 0, 0, 8, 8, Color::White)
+            .unwrap();
+        assert_eq!(epd.last_refresh_ms(), 10);
+
+        // without a confirm window, the busy pin isn't waited for
+        epd.set_busy_confirm_us(0);
+        epd.display_frame(&mut spi, &mut Delay).unwrap();
+        epd.wait_until_idle(&mut spi, &mut Delay).unwrap();
+        assert_eq!(epd.last_refresh_ms(), 0);
+    }
+
+    #[test]
+    fn several_partial_windows_one_refresh() {
+        let (mut epd, mut spi, bus) = epd();
+
+        epd.update_partial_frame(&mut spi, &mut Delay, &[0xFF; 16], 0, 0, 64, 2)
+            .unwrap();
+        epd.update_partial_frame(&mut spi, &mut Delay, &[0x00; 4], 384, 0, 16, 2)
+            .unwrap();
+        epd.display_frame(&mut spi, &mut Delay).unwrap();
+        assert_eq!(
+            commands(&bus),
+            [0x91, 0x90, 0x13, 0x92, 0x91, 0x90, 0x13, 0x92, 0x12]
+        );
+    }
+
+    #[test]
+    fn sleep_and_wake_up() {
+        let (mut epd, mut spi, bus) = epd();
+        assert!(!epd.is_sleeping());
+
+        epd.sleep(&mut spi, &mut Delay).unwrap();
+        assert!(epd.is_sleeping());
+        assert_eq!(commands(&bus).last(), Some(&0x07));
+
+        // sleeping twice doesn't send anything
+        bus.borrow_mut().sent.clear();
+        epd.sleep(&mut spi, &mut Delay).unwrap();
+        assert_eq!(commands(&bus), []);
+
+        // waiting doesn't send anything either, so it is fine while asleep
+        epd.wait_until_idle(&mut spi, &mut Delay).unwrap();
+
+        epd.wake_up(&mut spi, &mut Delay).unwrap();
+        assert!(!epd.is_sleeping());
+        epd.display_frame(&mut spi, &mut Delay).unwrap();
+    }
+
+    #[test]
+    fn shutdown() {
+        let (mut epd, mut spi, bus) = epd();
+
+        epd.shutdown(&mut spi, &mut Delay, true).unwrap();
+        assert!(epd.is_sleeping());
+        let commands = commands(&bus);
+        assert_eq!(commands[..4], [0x61, 0x10, 0x13, 0x12]);
+        assert_eq!(commands.last(), Some(&0x07));
+
+        // already asleep
+        bus.borrow_mut().sent.clear();
+        epd.shutdown(&mut spi, &mut Delay, true).unwrap();
+        assert!(bus.borrow().sent.is_empty());
+    }
+
+    #[test]
+    fn display_frame_while_sleeping() {
+        let (mut epd, mut spi, bus) = epd();
+        epd.sleep(&mut spi, &mut Delay).unwrap();
+        bus.borrow_mut().sent.clear();
+
+        let frame = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+        epd.update_frame(&mut spi, &frame, &mut Delay).unwrap();
+        epd.display_frame(&mut spi, &mut Delay).unwrap();
+        epd.display_test_pattern(&mut spi, &mut Delay, TestPattern::Border)
+            .unwrap();
+        // checked before the arguments, like in the other functions
+        epd.display_frames(&mut spi, &frame[1..], &frame, &mut Delay)
+            .unwrap();
+        assert!(bus.borrow().sent.is_empty());
+    }
+
+    #[test]
+    fn display_band() {
+        let (mut epd, mut spi, bus) = epd();
+        let band = [0xAA; WIDTH as usize / 8 * 16];
+
+        epd.display_band(&mut spi, &mut Delay, &band, 284, 16)
+            .unwrap();
+        assert_eq!(
+            commands(&bus),
+            [
+                0x20, 0x21, 0x22, 0x23, 0x24, 0x91, 0x90, 0x13, 0x12, 0x92, 0x20, 0x21, 0x22, 0x23,
+                0x24
+            ]
+        );
+        assert_eq!(
+            data_of(&bus, Command::PartialWindow),
+            [0x00, 0x00, 0x01, 0x8F, 0x01, 0x1C, 0x01, 0x2B, 0x01]
+        );
+        assert!(data_of(&bus, Command::DataStartTransmission2) == band);
+        assert_eq!(epd.current_lut(), Some(&LUTS_FULL));
+    }
+
+    #[test]
+    fn command_with_data_single_transaction() {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let mut spi = Spi(bus.clone());
+        let cs = Cs(bus.clone());
+        let mut epd =
+            Epd4in2::new(&mut spi, cs, Pin, Dc(bus.clone()), Pin, &mut Delay, None).unwrap();
+        bus.borrow_mut().transactions = 0;
+
+        epd.set_data_interval(&mut spi, 0x07).unwrap();
+        assert_eq!(bus.borrow().transactions, 1);
+        assert_eq!(data_of(&bus, Command::VcomAndDataIntervalSetting), [0x97]);
+    }
+
+    #[test]
+    fn dimensions() {
+        let (epd, _spi, _bus) = epd();
+        assert_eq!(epd.dimensions(), (400, 300));
+        #[cfg(feature = "graphics")]
+        assert_eq!(
+            epd.size(),
+            embedded_graphics_core::geometry::Size::new(400, 300)
+        );
+    }
+
+    #[test]
+    fn clear_on_init() {
+        let (mut epd, mut spi, bus) = epd();
+
+        epd.wake_up(&mut spi, &mut Delay).unwrap();
+        assert_ne!(commands(&bus).last(), Some(&0x12));
+
+        epd.set_clear_on_init(true);
+        bus.borrow_mut().sent.clear();
+        epd.wake_up(&mut spi, &mut Delay).unwrap();
+        assert_eq!(
+            commands(&bus)[commands(&bus).len() - 4..],
+            [0x61, 0x10, 0x13, 0x12]
+        );
+    }
+
+    #[test]
+    fn skip_old_frame() {
+        let (mut epd, mut spi, bus) = epd();
+        let buffer = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+
+        epd.update_frame(&mut spi, &buffer, &mut Delay).unwrap();
+        assert_eq!(commands(&bus), [0x10, 0x13]);
+
+        epd.set_skip_old_frame(true);
+        bus.borrow_mut().sent.clear();
+        epd.update_frame(&mut spi, &buffer, &mut Delay).unwrap();
+        assert_eq!(commands(&bus), [0x13]);
+        assert_eq!(bus.borrow().sent.len(), 1 + buffer.len());
+    }
+
+    #[test]
+    fn display_partial_with_lut() {
+        let (mut epd, mut spi, bus) = epd();
+        let mut lut = LUTS_QUICK;
+        lut.vcom[1] = 0x08;
+
+        epd.display_partial_with_lut(&mut spi, &mut Delay, &[0x00; 4], 8, 8, 16, 2, &lut, false)
+            .unwrap();
+        assert_eq!(
+            commands(&bus),
+            [0x20, 0x21, 0x22, 0x23, 0x24, 0x91, 0x90, 0x13, 0x12, 0x92]
+        );
+        assert_eq!(data_of(&bus, Command::LutForVcom)[..2], [0x00, 0x08]);
+        assert_eq!(epd.current_lut(), Some(&lut));
+
+        // the LUTs of the refresh mode are restored afterwards
+        bus.borrow_mut().sent.clear();
+        epd.display_partial_with_lut(&mut spi, &mut Delay, &[0x00; 4], 8, 8, 16, 2, &lut, true)
+            .unwrap();
+        assert_eq!(commands(&bus)[..5], [0x20, 0x21, 0x22, 0x23, 0x24]);
+        assert_eq!(epd.current_lut(), Some(&LUTS_FULL));
+    }
+
+    #[test]
+    fn spi_retries() {
+        let (mut epd, mut spi, bus) = epd();
+
+        bus.borrow_mut().failures = 1;
+        assert!(epd.power_on(&mut spi, &mut Delay).is_err());
+
+        epd.set_spi_retries(2);
+        bus.borrow_mut().failures = 2;
+        epd.power_on(&mut spi, &mut Delay).unwrap();
+        assert_eq!(commands(&bus), [0x04]);
+
+        bus.borrow_mut().failures = 3;
+        assert!(epd.power_on(&mut spi, &mut Delay).is_err());
+    }
+
+    #[test]
+    fn spi_retry_delay() {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let mut spi = Spi(bus.clone());
+        let mut delay = DelaySum::default();
+        let mut epd =
+            Epd4in2::new(&mut spi, Pin, Pin, Dc(bus.clone()), Pin, &mut delay, None).unwrap();
+        epd.set_spi_retries(2);
+        epd.set_spi_retry_delay_us(100);
+        let frame = [0xFF; WIDTH as usize / 8 * HEIGHT as usize];
+
+        // a failed write of the DTM1 fill
+        delay.0 = 0;
+        bus.borrow_mut().data_failures = 1;
+        epd.update_frame(&mut spi, &frame, &mut delay).unwrap();
+        assert_eq!(delay.0, 100);
+
+        // two failed writes of a frame buffer
+        delay.0 = 0;
+        bus.borrow_mut().data_failures = 2;
+        epd.display_frames(&mut spi, &frame, &frame, &mut delay)
+            .unwrap();
+        assert_eq!(delay.0, 200);
+    }
+
+    #[test]
+    fn clear_region_sequence() {
+        let (mut epd, mut spi, bus) = epd();
+
+        epd.clear_region(&mut spi, &mut Delay, 4, 8, 8, 2, Color::White)
+            .unwrap();
+
+        let luts = [0x20, 0x21, 0x22, 0x23, 0x24];
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&luts);
+        expected.extend_from_slice(&[0x91, 0x90, 0x10, 0x13, 0x12, 0x92]);
+        expected.extend_from_slice(&luts);
+        assert_eq!(commands(&bus), expected);
+
+        // widened to x = 0..16
+        assert_eq!(
+            data_of(&bus, Command::PartialWindow),
+            [0x00, 0x00, 0x00, 0x0F, 0x00, 0x08, 0x00, 0x09, 0x01]
+        );
+        assert_eq!(data_of(&bus, Command::DataStartTransmission2), [0xFF; 4]);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn take_log() {
+        use crate::testing::SpiEvent;
+
+        let (mut epd, mut spi, bus) = epd();
+        let _ = epd.take_log();
+        epd.set_data_interval(&mut spi, 0x03).unwrap();
+        let log = epd.take_log();
+        assert_eq!(log, [SpiEvent::Command(0x50), SpiEvent::Data(0x93)]);
+        // same as what the bus received
+        assert_eq!(bus.borrow().sent, [(true, 0x50), (false, 0x93)]);
+        assert!(epd.take_log().is_empty());
+    }
+
+    #[test]
+    fn record_and_replay() {
+        use crate::recording::*;
+
+        let mut buffer = [0u8; 32 * 1024];
+        let recorder = RefCell::new(Recorder::new(&mut buffer));
+        let mut rec_spi = RecordingSpi(&recorder);
+        let mut rec_delay = RecordingDelay(&recorder);
+        let mut recording = Epd4in2::new(
+            &mut rec_spi,
+            NoPin,
+            RecordingBusy(&recorder),
+            RecordingDc(&recorder),
+            RecordingRst(&recorder),
+            &mut rec_delay,
+            None,
+        )
+        .unwrap();
+        recording.clear_frame(&mut rec_spi, &mut rec_delay).unwrap();
+        recording.sleep(&mut rec_spi, &mut rec_delay).unwrap();
+        let recorder = recorder.into_inner();
+        let stream = recorder.stream().unwrap();
+
+        let (mut epd, mut spi, bus) = epd();
+        epd.init(&mut spi, &mut Delay).unwrap();
+        epd.clear_frame(&mut spi, &mut Delay).unwrap();
+        epd.sleep(&mut spi, &mut Delay).unwrap();
+        let expected = std::mem::take(&mut bus.borrow_mut().sent);
+
+        assert_eq!(epd.replay(&mut spi, &mut Delay, stream), Ok(stream.len()));
+        assert_eq!(bus.borrow().sent, expected);
+    }
+
+    #[test]
+    fn replay_truncated_stream() {
+        use crate::recording::*;
+
+        let (mut epd, mut spi, bus) = epd();
+        // a command, then data announcing 3 bytes with only 2 left
+        let stream = [OP_COMMAND, 0x50, OP_DATA, 3, 0, 0x97, 0x98];
+        assert_eq!(epd.replay(&mut spi, &mut Delay, &stream), Ok(2));
+        assert_eq!(bus.borrow().sent, [(true, 0x50)]);
+
+        // an unknown operation stops the replay too
+        bus.borrow_mut().sent.clear();
+        let stream = [OP_COMMAND, 0x50, 0xFF, OP_COMMAND, 0x12];
+        assert_eq!(epd.replay(&mut spi, &mut Delay, &stream), Ok(2));
+        assert_eq!(bus.borrow().sent, [(true, 0x50)]);
+    }
+
+    #[test]
+    fn new_uninitialized() {
+        let bus = Rc::new(RefCell::new(Bus::default()));
+        let mut spi = Spi(bus.clone());
+        let mut epd = TestEpd::new_uninitialized(Pin, Pin, Dc(bus.clone()), Pin, None);
+        assert!(bus.borrow().sent.is_empty());
+        assert_eq!(epd.current_lut(), None);
+
+        epd.wake_up(&mut spi, &mut Delay).unwrap();
+        assert_eq!(commands(&bus)[..2], [0x01, 0x06]);
+        assert_eq!(epd.current_lut(), Some(&LUTS_FULL));
+    }
+
+    #[test]
+    fn otp_lut_source() {
+        let (mut epd, mut spi, bus) = epd();
+
+        epd.set_lut_source(&mut spi, &mut Delay, LutSource::Otp)
+            .unwrap();
+        assert_eq!(commands(&bus), [0x00]);
+        assert_eq!(data_of(&bus, Command::PanelSetting), [0x1F]);
+
+        bus.borrow_mut().sent.clear();
+        epd.set_lut(&mut spi, &mut Delay, Some(RefreshLut::Quick))
+            .unwrap();
+        assert_eq!(commands(&bus), []);
+
+        epd.set_lut_source(&mut spi, &mut Delay, LutSource::Registers)
+            .unwrap();
+        assert_eq!(commands(&bus), [0x00, 0x20, 0x21, 0x22, 0x23, 0x24]);
+        assert_eq!(data_of(&bus, Command::PanelSetting), [0x3F]);
+        assert_eq!(epd.current_lut(), Some(&LUTS_QUICK));
+    }
+
+    #[test]
+    fn fast_refresh_sequence() {
+        let (mut epd, mut spi, bus) = epd();
+        let buffer = [0u8; WIDTH as usize / 8 * HEIGHT as usize];
+
+        epd.update_and_display_frame_fast(&mut spi, &buffer, &mut Delay)
+            .unwrap();
+
+        let luts = [0x20, 0x21, 0x22, 0x23, 0x24];
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&luts);
+        expected.extend_from_slice(&[0x13, 0x12, 0x13, 0x12]);
+        expected.extend_from_slice(&luts);
+        assert_eq!(commands(&bus), expected);
+
+        assert_eq!(data_of(&bus, Command::LutForVcom), LUT_VCOM0_FAST);
+        assert_eq!(data_of(&bus, Command::LutWhiteToWhite), LUT_WW_FAST);
+        assert_eq!(data_of(&bus, Command::LutBlackToBlack), LUT_BB_FAST);
+        assert_eq!(epd.current_lut(), Some(&LUTS_FULL));
+    }
 }
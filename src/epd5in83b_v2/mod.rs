@@ -14,7 +14,7 @@ use embedded_hal::{
 use crate::color::Color;
 use crate::interface::DisplayInterface;
 use crate::prelude::{TriColor, WaveshareDisplay, WaveshareThreeColorDisplay};
-use crate::traits::{InternalWiAdditions, RefreshLut};
+use crate::traits::{InternalWiAdditions, RefreshLut, SupportsPartial};
 
 pub(crate) mod command;
 use self::command::Command;
@@ -36,6 +36,10 @@ pub const WIDTH: u32 = 648;
 pub const HEIGHT: u32 = 480;
 /// Default Background Color
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+/// SPI mode of the display, the same as the crate wide [crate::SPI_MODE]
+pub const SPI_MODE: embedded_hal::spi::Mode = crate::SPI_MODE;
+/// Conservative default SPI clock speed, not tested with this display
+pub const SPI_MAX_SPEED_HZ: u32 = 4_000_000;
 const IS_BUSY_LOW: bool = true;
 const NUM_DISPLAY_BITS: u32 = WIDTH * HEIGHT / 8;
 
@@ -138,6 +142,8 @@ where
     }
 }
 
+impl<SPI, CS, BUSY, DC, RST, DELAY> SupportsPartial for Epd5in83<SPI, CS, BUSY, DC, RST, DELAY> {}
+
 impl<SPI, CS, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, CS, BUSY, DC, RST, DELAY>
     for Epd5in83<SPI, CS, BUSY, DC, RST, DELAY>
 where
@@ -202,6 +208,7 @@ where
         buffer: &[u8],
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
+        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
         self.wait_until_idle(spi, delay)?;
         self.update_achromatic_frame(spi, delay, buffer)?;
         let color = self.color.get_byte_value();
@@ -221,9 +228,7 @@ where
         height: u32,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        if buffer.len() as u32 != width / 8 * height {
-            //TODO panic or error
-        }
+        assert!(buffer.len() == buffer_len(width as usize, height as usize));
 
         let hrst_upper = (x / 8) as u8 >> 6;
         let hrst_lower = ((x / 8) << 3) as u8;
@@ -244,8 +249,8 @@ where
                 vred_lower, pt_scan,
             ],
         )?;
-        self.command(spi, Command::DataStartTransmission1)?;
-        self.send_data(spi, buffer)?;
+        self.interface
+            .cmd_with_rows(spi, Command::DataStartTransmission1, buffer, width)?;
 
         let color = TriColor::Black.get_byte_value(); //We need it black, so red channel will be rendered transparent
         self.command(spi, Command::DataStartTransmission2)?;
@@ -1,8 +1,11 @@
 //! Graphics Support for EPDs
 
-use crate::color::{ColorType, TriColor};
+use crate::color::{Color, ColorType, Gray4, TriColor};
+use crate::pixelbuf::{get_bit, set_bit};
 use core::marker::PhantomData;
+use embedded_graphics_core::pixelcolor::BinaryColor;
 use embedded_graphics_core::prelude::*;
+use embedded_graphics_core::primitives::Rectangle;
 
 /// Display rotation, only 90° increments supported
 #[derive(Clone, Copy, Default)]
@@ -142,6 +145,10 @@ impl<
     ///
     /// This only concerns future drawing made to it. Anything aready drawn
     /// stays as it is in the buffer.
+    ///
+    /// The `size()` used for drawing is the logical one, so width and height are swapped
+    /// for 90 and 270 degrees. The buffer keeps its physical layout for the transfer to
+    /// the display.
     pub fn set_rotation(&mut self, rotation: DisplayRotation) {
         self.rotation = rotation;
     }
@@ -151,6 +158,26 @@ impl<
         self.rotation
     }
 
+    /// Iterates over the rows of the internal buffer
+    ///
+    /// Every row takes `(width * bits per pixel + 7) / 8` bytes. If that isn't a whole
+    /// number of bytes, the last byte of a row ends with padding bits which are never
+    /// drawn to and ignored by the displays.
+    ///
+    /// The rows are in buffer order, the rotation isn't taken into account. For colors
+    /// using two buffers (like [TriColor]) the rows of the black/white buffer are followed
+    /// by the rows of the chromatic buffer.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.buffer
+            .chunks(line_bytes(WIDTH, COLOR::BITS_PER_PIXEL_PER_BUFFER))
+    }
+
+    /// Same as [rows](Display::rows), but the rows can be modified
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        self.buffer
+            .chunks_mut(line_bytes(WIDTH, COLOR::BITS_PER_PIXEL_PER_BUFFER))
+    }
+
     /// Set a specific pixel color on this display
     pub fn set_pixel(&mut self, pixel: Pixel<COLOR>) {
         set_pixel(
@@ -179,6 +206,91 @@ impl<const WIDTH: u32, const HEIGHT: u32, const BWRBIT: bool, const BYTECOUNT: u
     }
 }
 
+/// Some Gray4 specifics
+impl<const WIDTH: u32, const HEIGHT: u32, const BWRBIT: bool, const BYTECOUNT: usize>
+    Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, Gray4>
+{
+    /// Get the color of a specific pixel, None if it is outside of the display
+    pub fn pixel(&self, point: Point) -> Option<Gray4> {
+        let (x, y) = buffer_position(point, WIDTH, HEIGHT, self.rotation)?;
+        let index = x as usize / 4 + y as usize * line_bytes(WIDTH, 2);
+        let shift = 6 - 2 * (x % 4);
+        Some(Gray4::from_bits(self.buffer[index] >> shift))
+    }
+
+    /// Get a draw target with twice the resolution, for anti-aliased drawing
+    ///
+    /// See [Supersampled] for details.
+    pub fn supersampled(&mut self) -> Supersampled<'_, WIDTH, HEIGHT, BWRBIT, BYTECOUNT> {
+        Supersampled { display: self }
+    }
+}
+
+/// Draw target with twice the resolution of a [Gray4] display, returned by
+/// [Display::supersampled]
+///
+/// Every pixel of the display is made of 2x2 subpixels. Each `BinaryColor::On` subpixel
+/// drawn makes the pixel one gray level darker, so a fully covered pixel turns black
+/// while the edges of shapes get intermediate gray levels. `BinaryColor::Off` pixels
+/// are transparent and left untouched.
+///
+/// This smooths the edges of text and other shapes, everything just needs to be drawn
+/// at twice the size. E.g. text drawn with a 10x20 font ends up as smooth 5x10 text:
+///
+/// ```rust
+/// # use embedded_graphics::{mono_font::{ascii::FONT_10X20, MonoTextStyle}, pixelcolor::BinaryColor, prelude::*, text::Text};
+/// # use epd_waveshare::{color::Gray4, graphics::Display};
+/// let mut display = Display::<64, 16, false, { 64 * 2 / 8 * 16 }, Gray4>::default();
+/// display.clear(Gray4::White).ok();
+///
+/// let style = MonoTextStyle::new(&FONT_10X20, BinaryColor::On);
+/// let _ = Text::new("Hello", Point::new(0, 16), style).draw(&mut display.supersampled());
+/// ```
+///
+/// Every subpixel should only be drawn once, as drawing it twice darkens its pixel twice.
+pub struct Supersampled<
+    'a,
+    const WIDTH: u32,
+    const HEIGHT: u32,
+    const BWRBIT: bool,
+    const BYTECOUNT: usize,
+> {
+    display: &'a mut Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, Gray4>,
+}
+
+/// For use with embedded_grahics
+impl<const WIDTH: u32, const HEIGHT: u32, const BWRBIT: bool, const BYTECOUNT: usize> DrawTarget
+    for Supersampled<'_, WIDTH, HEIGHT, BWRBIT, BYTECOUNT>
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if color == BinaryColor::Off || point.x < 0 || point.y < 0 {
+                continue;
+            }
+            let point = Point::new(point.x / 2, point.y / 2);
+            if let Some(gray) = self.display.pixel(point) {
+                self.display.set_pixel(Pixel(point, gray.darker()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// For use with embedded_grahics
+impl<const WIDTH: u32, const HEIGHT: u32, const BWRBIT: bool, const BYTECOUNT: usize>
+    OriginDimensions for Supersampled<'_, WIDTH, HEIGHT, BWRBIT, BYTECOUNT>
+{
+    fn size(&self) -> Size {
+        self.display.size() * 2
+    }
+}
+
 /// Same as `Display`, except that its characteristics are defined at runtime.
 /// See display for documentation as everything is the same except that default
 /// is replaced by a `new` method.
@@ -257,10 +369,8 @@ impl<'a, COLOR: ColorType> VarDisplay<'a, COLOR> {
     /// get the number of used bytes in the buffer
     fn buffer_size(&self) -> usize {
         self.height as usize
-            * line_bytes(
-                self.width,
-                COLOR::BITS_PER_PIXEL_PER_BUFFER * COLOR::BUFFER_COUNT,
-            )
+            * line_bytes(self.width, COLOR::BITS_PER_PIXEL_PER_BUFFER)
+            * COLOR::BUFFER_COUNT
     }
 
     /// get internal buffer to use it (to draw in epd)
@@ -272,6 +382,10 @@ impl<'a, COLOR: ColorType> VarDisplay<'a, COLOR> {
     ///
     /// This only concerns future drawing made to it. Anything aready drawn
     /// stays as it is in the buffer.
+    ///
+    /// The `size()` used for drawing is the logical one, so width and height are swapped
+    /// for 90 and 270 degrees. The buffer keeps its physical layout for the transfer to
+    /// the display.
     pub fn set_rotation(&mut self, rotation: DisplayRotation) {
         self.rotation = rotation;
     }
@@ -281,6 +395,26 @@ impl<'a, COLOR: ColorType> VarDisplay<'a, COLOR> {
         self.rotation
     }
 
+    /// Iterates over the rows of the internal buffer
+    ///
+    /// Every row takes `(width * bits per pixel + 7) / 8` bytes. If that isn't a whole
+    /// number of bytes, the last byte of a row ends with padding bits which are never
+    /// drawn to and ignored by the displays.
+    ///
+    /// The rows are in buffer order, the rotation isn't taken into account. For colors
+    /// using two buffers (like [TriColor]) the rows of the black/white buffer are followed
+    /// by the rows of the chromatic buffer.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.buffer()
+            .chunks(line_bytes(self.width, COLOR::BITS_PER_PIXEL_PER_BUFFER))
+    }
+
+    /// Same as [rows](VarDisplay::rows), but the rows can be modified
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        let size = self.buffer_size();
+        self.buffer[..size].chunks_mut(line_bytes(self.width, COLOR::BITS_PER_PIXEL_PER_BUFFER))
+    }
+
     /// Set a specific pixel color on this display
     pub fn set_pixel(&mut self, pixel: Pixel<COLOR>) {
         let size = self.buffer_size();
@@ -309,19 +443,13 @@ impl<'a> VarDisplay<'a, TriColor> {
 }
 
 // This is a function to share code between `Display` and `VarDisplay`
-// It sets a specific pixel in a buffer to a given color.
-// The big number of parameters is due to the fact that it is an internal function to both
-// strctures.
-fn set_pixel<COLOR: ColorType>(
-    buffer: &mut [u8],
+// Position of a point in the buffer, None if it is out of range
+fn buffer_position(
+    point: Point,
     width: u32,
     height: u32,
     rotation: DisplayRotation,
-    bwrbit: bool,
-    pixel: Pixel<COLOR>,
-) {
-    let Pixel(point, color) = pixel;
-
+) -> Option<(u32, u32)> {
     // final coordinates
     let (x, y) = match rotation {
         // as i32 = never use more than 2 billion pixel per line or per column
@@ -333,13 +461,33 @@ fn set_pixel<COLOR: ColorType>(
 
     // Out of range check
     if (x < 0) || (x >= width as i32) || (y < 0) || (y >= height as i32) {
-        // don't do anything in case of out of range
-        return;
+        return None;
     }
+    Some((x as u32, y as u32))
+}
+
+// It sets a specific pixel in a buffer to a given color.
+// The big number of parameters is due to the fact that it is an internal function to both
+// strctures.
+fn set_pixel<COLOR: ColorType>(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    rotation: DisplayRotation,
+    bwrbit: bool,
+    pixel: Pixel<COLOR>,
+) {
+    let Pixel(point, color) = pixel;
+
+    let (x, y) = match buffer_position(point, width, height, rotation) {
+        Some(position) => position,
+        // don't do anything in case of out of range
+        None => return,
+    };
 
     let index = x as usize * COLOR::BITS_PER_PIXEL_PER_BUFFER / 8
         + y as usize * line_bytes(width, COLOR::BITS_PER_PIXEL_PER_BUFFER);
-    let (mask, bits) = color.bitmask(bwrbit, x as u32);
+    let (mask, bits) = color.bitmask(bwrbit, x);
 
     if COLOR::BUFFER_COUNT == 2 {
         // split buffer is for tricolor displays that use 2 buffer for 2 bits per pixel
@@ -351,6 +499,873 @@ fn set_pixel<COLOR: ColorType>(
     }
 }
 
+/// Draws a 1 bit per pixel image onto a display
+///
+/// - `image` is stored row by row, every row taking `(width + 7) / 8` bytes, the
+///   padding bits at the end of a row are ignored
+/// - bits are read MSB first, the most significant bit being the leftmost pixel
+/// - every set bit is drawn in `color`, cleared bits are left untouched (transparent)
+///
+/// `position` is the top left corner of the image and may be anywhere, even partially
+/// outside of the display. Everything outside of the display is clipped.
+pub fn draw_image<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    width: u32,
+    height: u32,
+    image: &[u8],
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let source = Rectangle::new(Point::zero(), Size::new(width, height));
+    draw_bits(display, position, image, width, source, 1, color, |x| {
+        0x80 >> (x % 8)
+    })
+}
+
+/// Draws a 1 bit per pixel image enlarged by an integer factor onto a display
+///
+/// Works like [draw_image], but every pixel of `image` is drawn as a `scale` x `scale`
+/// block (nearest neighbor scaling), e.g. a 16x16 icon ends up 48x48 pixels big with a
+/// `scale` of 3. `width` and `height` are the size of `image`, not the scaled size.
+pub fn draw_image_scaled<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    width: u32,
+    height: u32,
+    image: &[u8],
+    scale: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let source = Rectangle::new(Point::zero(), Size::new(width, height));
+    draw_bits(display, position, image, width, source, scale, color, |x| {
+        0x80 >> (x % 8)
+    })
+}
+
+/// Draws a XBM image onto a display
+///
+/// XBM is the 1 bit per pixel format exported by e.g. ImageMagick (`convert icon.png icon.xbm`)
+/// or GIMP. `xbm` is the content of the `_bits` array of such a file.
+///
+/// Everything works like in [draw_image], except for the bit order: XBM stores the
+/// leftmost pixel in the least significant bit of a byte (LSB first), while
+/// [draw_image] and the display buffers use MSB first.
+pub fn draw_xbm<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    width: u32,
+    height: u32,
+    xbm: &[u8],
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let source = Rectangle::new(Point::zero(), Size::new(width, height));
+    draw_bits(display, position, xbm, width, source, 1, color, |x| {
+        0x01 << (x % 8)
+    })
+}
+
+/// Draws a single tile of an atlas of equally sized 1 bit per pixel images
+///
+/// `atlas` is laid out like the images of [draw_image] and is `atlas_width` pixels wide.
+/// It is split into tiles of `tile_width` x `tile_height` pixels, which are numbered
+/// row by row, starting with 0 at the top left. Tile `index` is drawn with its top left
+/// corner at `position`, clipped to the display like in [draw_image].
+///
+/// Nothing is drawn if `index` is outside of the atlas.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_from_atlas<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    atlas: &[u8],
+    atlas_width: u32,
+    tile_width: u32,
+    tile_height: u32,
+    index: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let tiles_per_row = atlas_width.checked_div(tile_width).unwrap_or(0);
+    if tiles_per_row == 0 {
+        return Ok(());
+    }
+    let top_left = Point::new(
+        (index % tiles_per_row * tile_width) as i32,
+        (index / tiles_per_row * tile_height) as i32,
+    );
+    let source = Rectangle::new(top_left, Size::new(tile_width, tile_height));
+    draw_bits(
+        display,
+        position,
+        atlas,
+        atlas_width,
+        source,
+        1,
+        color,
+        |x| 0x80 >> (x % 8),
+    )
+}
+
+/// A set of equally sized 1 bit per pixel icons, looked up by their code
+///
+/// Like an icon font (e.g. Material Icons or Font Awesome rendered to bitmaps), every
+/// glyph is an image laid out like in [draw_image], `width` x `height` pixels big.
+#[derive(Clone, Copy, Debug)]
+pub struct IconFont<'a> {
+    /// The code of every icon together with its image
+    pub glyphs: &'a [(u16, &'a [u8])],
+    /// Width of every icon in pixels
+    pub width: u32,
+    /// Height of every icon in pixels
+    pub height: u32,
+}
+
+impl<'a> IconFont<'a> {
+    /// Returns the image of the icon with `code`, None if the font doesn't contain it
+    pub fn glyph(&self, code: u16) -> Option<&'a [u8]> {
+        self.glyphs
+            .iter()
+            .find(|(glyph_code, _)| *glyph_code == code)
+            .map(|(_, image)| *image)
+    }
+}
+
+/// Draws the icon with `code` of an [IconFont] onto a display
+///
+/// The icon is drawn with its top left corner at `position` and clipped like in
+/// [draw_image]. If the font doesn't contain `code`, the outline of a box of the icon
+/// size is drawn instead, so missing icons stand out.
+pub fn draw_icon<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    font: &IconFont<'_>,
+    code: u16,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    match font.glyph(code) {
+        Some(image) => draw_image(display, position, font.width, font.height, image, color),
+        None => {
+            let (right, bottom) = (font.width as i32 - 1, font.height as i32 - 1);
+            let bounds = display.bounding_box();
+            let pixels = Rectangle::new(position, Size::new(font.width, font.height))
+                .points()
+                .filter(|point| {
+                    let Point { x, y } = *point - position;
+                    x == 0 || y == 0 || x == right || y == bottom
+                })
+                .filter(|point| bounds.contains(*point))
+                .map(|point| Pixel(point, color));
+            display.draw_iter(pixels)
+        }
+    }
+}
+
+/// Copies a rectangular region from one 1 bit per pixel buffer into another one
+///
+/// Both buffers are laid out like the display buffers: `(width + 7) / 8` bytes per row,
+/// MSB first. Their heights are derived from their lengths.
+///
+/// The `width` x `height` region at (`src_x`, `src_y`) of `src` is copied to
+/// (`dst_x`, `dst_y`) of `dst`. Neither position needs to be a multiple of 8, the bits
+/// are shifted as needed. Set and cleared bits are both copied. Everything outside of
+/// either buffer is clipped.
+#[allow(clippy::too_many_arguments)]
+pub fn blit(
+    dst: &mut [u8],
+    dst_width: u32,
+    dst_x: u32,
+    dst_y: u32,
+    src: &[u8],
+    src_width: u32,
+    src_x: u32,
+    src_y: u32,
+    width: u32,
+    height: u32,
+) {
+    let dst_line = line_bytes(dst_width, 1);
+    let src_line = line_bytes(src_width, 1);
+    if dst_line == 0 || src_line == 0 {
+        return;
+    }
+    let dst_height = (dst.len() / dst_line) as u32;
+    let src_height = (src.len() / src_line) as u32;
+
+    let width = width
+        .min(dst_width.saturating_sub(dst_x))
+        .min(src_width.saturating_sub(src_x));
+    let height = height
+        .min(dst_height.saturating_sub(dst_y))
+        .min(src_height.saturating_sub(src_y));
+
+    for y in 0..height {
+        for x in 0..width {
+            let set = get_bit(src, src_line, src_x + x, src_y + y);
+            set_bit(dst, dst_line, dst_x + x, dst_y + y, set);
+        }
+    }
+}
+
+/// Draws a checkbox widget onto a display
+///
+/// The box is a `size` x `size` outline with its top left corner at `position`. If
+/// `checked` is set, a check mark is drawn inside of it. Everything outside of the
+/// display is clipped, the inner pixels of an unchecked box are left untouched.
+pub fn draw_checkbox<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    size: u32,
+    checked: bool,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let last = size as i32 - 1;
+    // the check mark is made of two 45° legs, the right one twice as long as the left one
+    let margin = (size as i32 / 5).max(2);
+    let inner = size as i32 - 2 * margin;
+    let (bottom, left_leg) = (last - margin, (inner - 1) / 3);
+    draw_widget(display, position, size, color, |x, y| {
+        let outline = x == 0 || y == 0 || x == last || y == last;
+        let corner = margin + left_leg;
+        let check = if inner < 3 {
+            // too small for a check mark, fill the box instead
+            x > 0 && y > 0 && x < last && y < last
+        } else if (margin..corner).contains(&x) {
+            y == bottom - (corner - x)
+        } else if (corner..margin + inner).contains(&x) {
+            y == bottom - (x - corner)
+        } else {
+            false
+        };
+        outline || (checked && check)
+    })
+}
+
+/// Draws a radio button widget onto a display
+///
+/// The button is a circle outline with a diameter of `size` pixels, `position` being the
+/// top left corner of its bounding box. If `selected` is set, a filled dot with half the
+/// diameter is drawn in its center. Everything outside of the display is clipped.
+pub fn draw_radio<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    size: u32,
+    selected: bool,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let size = size as i32;
+    let dot = size / 2;
+    draw_widget(display, position, size as u32, color, |x, y| {
+        // distance to the center in half pixels, to keep everything in integers
+        let (dx, dy) = (2 * x + 1 - size, 2 * y + 1 - size);
+        let distance = dx * dx + dy * dy;
+        let outline = distance <= size * size && distance > (size - 2) * (size - 2);
+        outline || (selected && distance <= dot * dot)
+    })
+}
+
+/// Draws a filled ring, e.g. for gauges and progress rings
+///
+/// All the pixels whose distance to `center` is between `inner_radius` and
+/// `outer_radius` (both included) are set, so unlike concentric circles the ring has no
+/// gaps. The radii are swapped if `inner_radius` is the larger one, an `inner_radius` of 0
+/// gives a filled circle. Pixels outside of the display are skipped.
+pub fn draw_ring<D: DrawTarget>(
+    display: &mut D,
+    center: Point,
+    inner_radius: u32,
+    outer_radius: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let (inner, outer) = if inner_radius > outer_radius {
+        (outer_radius as i32, inner_radius as i32)
+    } else {
+        (inner_radius as i32, outer_radius as i32)
+    };
+    let bounds = display.bounding_box();
+    let size = 2 * outer as u32 + 1;
+    let pixels = Rectangle::new(center - Point::new(outer, outer), Size::new(size, size))
+        .points()
+        .filter(|point| bounds.contains(*point))
+        .filter(|&point| {
+            let Point { x, y } = point - center;
+            let distance = x * x + y * y;
+            inner * inner <= distance && distance <= outer * outer
+        })
+        .map(|point| Pixel(point, color));
+    display.draw_iter(pixels)
+}
+
+/// Shapes of markers drawn by [draw_marker]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Marker {
+    /// Diagonal cross, `x`
+    Cross,
+    /// Horizontal and vertical line, `+`
+    Plus,
+    /// Filled circle
+    Dot,
+    /// Square outline
+    Square,
+}
+
+/// Draws a marker, e.g. for data points of a chart
+///
+/// The marker is `size` x `size` pixels and centered at `center`, odd sizes center
+/// exactly. Parts outside of the display are skipped.
+pub fn draw_marker<D: DrawTarget>(
+    display: &mut D,
+    center: Point,
+    kind: Marker,
+    size: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let half = size as i32 / 2;
+    let position = center - Point::new(half, half);
+    let last = size as i32 - 1;
+    draw_widget(display, position, size, color, |x, y| match kind {
+        Marker::Cross => x == y || x + y == last,
+        Marker::Plus => x == half || y == half,
+        Marker::Dot => {
+            // distance to the center in half pixels, to keep everything in integers
+            let (dx, dy) = (2 * x - last, 2 * y - last);
+            dx * dx + dy * dy <= (last + 1) * (last + 1)
+        }
+        Marker::Square => x == 0 || y == 0 || x == last || y == last,
+    })
+}
+
+/// Draws a filled box with a 1 pixel border and optionally rounded corners
+///
+/// The common background of widgets: `area` is filled with `fill` and its outline is
+/// drawn in `border`. A `radius` of 0 gives square corners, larger radii are limited to
+/// half of the smaller side of `area`. Pixels outside of the display are skipped.
+pub fn draw_panel<D: DrawTarget>(
+    display: &mut D,
+    area: &Rectangle,
+    fill: D::Color,
+    border: D::Color,
+    radius: u32,
+) -> Result<(), D::Error> {
+    let Size { width, height } = area.size;
+    let (w, h) = (width as i32, height as i32);
+    let r = radius.min(width / 2).min(height / 2) as i32;
+    let bounds = display.bounding_box();
+    let pixels = area
+        .points()
+        .filter(|point| bounds.contains(*point))
+        .filter_map(|point| {
+            let Point { x, y } = point - area.top_left;
+            // distance to the center of the nearest corner circle in half pixels,
+            // 0 along the straight edges
+            let dx = 2 * x + 1 - (2 * x + 1).clamp(2 * r, 2 * w - 2 * r);
+            let dy = 2 * y + 1 - (2 * y + 1).clamp(2 * r, 2 * h - 2 * r);
+            let distance = dx * dx + dy * dy;
+            if distance > 4 * r * r {
+                return None;
+            }
+            let edge = x == 0 || y == 0 || x == w - 1 || y == h - 1;
+            let corner = r > 0 && distance > (2 * r - 2) * (2 * r - 2);
+            Some(Pixel(point, if edge || corner { border } else { fill }))
+        });
+    display.draw_iter(pixels)
+}
+
+/// Draws a dotted grid over the whole display
+///
+/// A pixel is set at every grid intersection, starting at the top left corner and
+/// repeating every `spacing_x` pixels horizontally and `spacing_y` pixels vertically.
+/// Nothing is drawn if one of the spacings is 0.
+///
+/// The grid covers the bounding box of `display`, so for a clipped draw target (see
+/// `DrawTargetExt::clipped` of embedded-graphics) only the clipping area is covered and
+/// the grid starts at its top left corner.
+pub fn draw_grid<D: DrawTarget>(
+    display: &mut D,
+    spacing_x: u32,
+    spacing_y: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    if spacing_x == 0 || spacing_y == 0 {
+        return Ok(());
+    }
+    let area = display.bounding_box();
+    let Size { width, height } = area.size;
+    let pixels = (0..height as i32)
+        .step_by(spacing_y as usize)
+        .flat_map(|y| {
+            (0..width as i32)
+                .step_by(spacing_x as usize)
+                .map(move |x| Point::new(x, y))
+        })
+        .map(|point| Pixel(area.top_left + point, color));
+    display.draw_iter(pixels)
+}
+
+/// Same as [draw_grid], but draws full lines instead of only their intersections
+pub fn draw_grid_lines<D: DrawTarget>(
+    display: &mut D,
+    spacing_x: u32,
+    spacing_y: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    if spacing_x == 0 || spacing_y == 0 {
+        return Ok(());
+    }
+    let area = display.bounding_box();
+    let Size { width, height } = area.size;
+    let rows = (0..height as i32)
+        .step_by(spacing_y as usize)
+        .flat_map(|y| (0..width as i32).map(move |x| Point::new(x, y)));
+    let columns = (0..width as i32)
+        .step_by(spacing_x as usize)
+        .flat_map(|x| (0..height as i32).map(move |y| Point::new(x, y)));
+    let pixels = rows
+        .chain(columns)
+        .map(|point| Pixel(area.top_left + point, color));
+    display.draw_iter(pixels)
+}
+
+/// Draws a dashed line from `start` to `end`, both included
+///
+/// The dash pattern starts at `start` with `on` set pixels followed by `off` skipped
+/// pixels, counted along the line. Nothing is drawn if `on` is 0, the line is solid if
+/// `off` is 0. Pixels outside of the display are skipped.
+pub fn draw_dashed_line<D: DrawTarget>(
+    display: &mut D,
+    start: Point,
+    end: Point,
+    on: u32,
+    off: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    draw_dashed(display, line_points(start, end), on, off, color)
+}
+
+/// Draws the outline of `area` with a dash pattern, e.g. for focus indicators
+///
+/// The pattern is the same as in [draw_dashed_line]. It starts at the top left corner
+/// and runs clockwise around the outline, continuing over the corners instead of
+/// restarting on each edge. Drawing it again with a pattern shifted by one pixel
+/// (e.g. `on` and `off` swapped for equal lengths) gives a marching ants effect.
+pub fn draw_dashed_rectangle<D: DrawTarget>(
+    display: &mut D,
+    area: &Rectangle,
+    on: u32,
+    off: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let bottom_right = match area.bottom_right() {
+        Some(bottom_right) => bottom_right,
+        None => return Ok(()),
+    };
+    let top_left = area.top_left;
+    let top_right = Point::new(bottom_right.x, top_left.y);
+    let bottom_left = Point::new(top_left.x, bottom_right.y);
+    if top_left.x == bottom_right.x || top_left.y == bottom_right.y {
+        // a single line, which would otherwise be drawn twice
+        return draw_dashed_line(display, top_left, bottom_right, on, off, color);
+    }
+    // each edge without its first point, which is the last point of the previous edge
+    let outline = line_points(top_left, top_right)
+        .chain(line_points(top_right, bottom_right).skip(1))
+        .chain(line_points(bottom_right, bottom_left).skip(1))
+        .chain(line_points(bottom_left, top_left).skip(1))
+        .take((2 * (area.size.width + area.size.height) - 4) as usize);
+    draw_dashed(display, outline, on, off, color)
+}
+
+fn draw_dashed<D: DrawTarget>(
+    display: &mut D,
+    points: impl Iterator<Item = Point>,
+    on: u32,
+    off: u32,
+    color: D::Color,
+) -> Result<(), D::Error> {
+    let period = on as u64 + off as u64;
+    let bounds = display.bounding_box();
+    let pixels = (0u64..)
+        .zip(points)
+        .filter(|(i, point)| i % period.max(1) < on as u64 && bounds.contains(*point))
+        .map(|(_, point)| Pixel(point, color));
+    display.draw_iter(pixels)
+}
+
+/// Shades of gray for black and white displays, drawn as dither patterns by [fill_shade]
+///
+/// At a distance the patterns read as gray. For displays showing real grays see
+/// [Gray4].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shade {
+    /// All pixels black
+    Black,
+    /// 3 of 4 pixels black
+    DarkGray,
+    /// Every other pixel black, a checkerboard
+    Gray,
+    /// 1 of 4 pixels black
+    LightGray,
+    /// All pixels white
+    White,
+}
+
+impl Shade {
+    /// Color of the pattern at a position of the display
+    fn color_at(self, point: Point) -> Color {
+        let (odd_x, odd_y) = (point.x & 1 == 1, point.y & 1 == 1);
+        let black = match self {
+            Shade::Black => true,
+            Shade::DarkGray => !(odd_x && odd_y),
+            Shade::Gray => odd_x == odd_y,
+            Shade::LightGray => !odd_x && !odd_y,
+            Shade::White => false,
+        };
+        if black {
+            Color::Black
+        } else {
+            Color::White
+        }
+    }
+}
+
+/// Fills an area with the dither pattern of a [Shade]
+///
+/// The pattern is aligned to the display and not to the area, so neighbouring fills of
+/// the same shade join seamlessly. The area is clipped to the display.
+pub fn fill_shade<D: DrawTarget<Color = Color>>(
+    display: &mut D,
+    area: &Rectangle,
+    shade: Shade,
+) -> Result<(), D::Error> {
+    let area = area.intersection(&display.bounding_box());
+    display.fill_contiguous(&area, area.points().map(|point| shade.color_at(point)))
+}
+
+/// Draws a histogram with its axes into `area`
+///
+/// The left column and the bottom row of `area` are the axes, drawn in black. The rest
+/// is split into equally wide bars, one for each of the `bins`, scaled so the largest
+/// bin fills the whole height. The bars are filled with `shade` and separated by a 1
+/// pixel gap if they are wider than 2 pixels. Bins of 0 are not drawn, neither are any
+/// bars if `bins` is empty, all the bins are 0 or `area` is too narrow for them.
+pub fn draw_histogram<D: DrawTarget<Color = Color>>(
+    display: &mut D,
+    area: &Rectangle,
+    bins: &[u16],
+    shade: Shade,
+) -> Result<(), D::Error> {
+    let Size { width, height } = area.size;
+    if width == 0 || height == 0 {
+        return Ok(());
+    }
+    let bottom = area.top_left.y + height as i32 - 1;
+    let axes = Rectangle::new(area.top_left, Size::new(1, height))
+        .points()
+        .chain(Rectangle::new(Point::new(area.top_left.x, bottom), Size::new(width, 1)).points());
+    display.draw_iter(axes.map(|point| Pixel(point, Color::Black)))?;
+
+    let (plot_width, plot_height) = (width - 1, height - 1);
+    let max = bins.iter().copied().max().unwrap_or(0) as u32;
+    if max == 0 || plot_width < bins.len() as u32 {
+        return Ok(());
+    }
+    let bin_width = plot_width / bins.len() as u32;
+    let bar_width = if bin_width > 2 {
+        bin_width - 1
+    } else {
+        bin_width
+    };
+    for (i, &bin) in bins.iter().enumerate() {
+        let bar_height = bin as u32 * plot_height / max;
+        let left = area.top_left.x + 1 + (i as u32 * bin_width) as i32;
+        let bar = Rectangle::new(
+            Point::new(left, bottom - bar_height as i32),
+            Size::new(bar_width, bar_height),
+        );
+        fill_shade(display, &bar, shade)?;
+    }
+    Ok(())
+}
+
+/// The last `N` samples of a metric, drawn as a small line chart
+///
+/// New samples are added with [push](Sparkline::push), replacing the oldest one once
+/// `N` samples are stored, so the drawn series scrolls to the left. Redraw it by clearing
+/// its area and calling [draw](Sparkline::draw), e.g. followed by a partial refresh of
+/// just this area.
+#[derive(Clone, Debug)]
+pub struct Sparkline<const N: usize> {
+    samples: [i32; N],
+    /// index of the oldest sample
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> Default for Sparkline<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Sparkline<N> {
+    /// Creates an empty sparkline
+    pub const fn new() -> Self {
+        Sparkline {
+            samples: [0; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Adds a sample, dropping the oldest one if `N` samples are stored already
+    pub fn push(&mut self, value: i32) {
+        if N == 0 {
+            return;
+        }
+        if self.len < N {
+            self.samples[(self.start + self.len) % N] = value;
+            self.len += 1;
+        } else {
+            self.samples[self.start] = value;
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    /// Returns the number of stored samples
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no samples are stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the stored samples, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = i32> + '_ {
+        (0..self.len).map(move |i| self.samples[(self.start + i) % N])
+    }
+
+    /// Draws the samples as connected lines into `area`
+    ///
+    /// The samples are spread over the whole width of `area`, the oldest one at the left
+    /// edge. They are scaled so the smallest one is at the bottom and the largest one at
+    /// the top, all equal samples are drawn at the bottom. Only the lines are drawn, the
+    /// background is left untouched. Pixels outside of the display are skipped.
+    pub fn draw<D: DrawTarget>(
+        &self,
+        display: &mut D,
+        area: &Rectangle,
+        color: D::Color,
+    ) -> Result<(), D::Error> {
+        let Size { width, height } = area.size;
+        if width == 0 || height == 0 || self.is_empty() {
+            return Ok(());
+        }
+        let min = self.samples().min().unwrap_or(0) as i64;
+        let max = self.samples().max().unwrap_or(0) as i64;
+        let last = (self.len - 1).max(1) as i64;
+        let point = |i: usize, value: i32| {
+            let x = i as i64 * (width as i64 - 1) / last;
+            let y = if max > min {
+                (value as i64 - min) * (height as i64 - 1) / (max - min)
+            } else {
+                0
+            };
+            area.top_left + Point::new(x as i32, height as i32 - 1 - y as i32)
+        };
+
+        let bounds = display.bounding_box();
+        let mut previous = None;
+        for (i, value) in self.samples().enumerate() {
+            let current = point(i, value);
+            let start = previous.unwrap_or(current);
+            let pixels = line_points(start, current)
+                .filter(|point| bounds.contains(*point))
+                .map(|point| Pixel(point, color));
+            display.draw_iter(pixels)?;
+            previous = Some(current);
+        }
+        Ok(())
+    }
+}
+
+// Points of the line from `start` to `end`, both included
+fn line_points(start: Point, end: Point) -> impl Iterator<Item = Point> {
+    let delta = end - start;
+    let steps = delta.x.abs().max(delta.y.abs());
+    (0..=steps).map(move |step| match steps {
+        0 => start,
+        // rounded to the nearest pixel
+        _ => {
+            let offset = |d: i32| (2 * d * step + steps * d.signum()) / (2 * steps);
+            start + Point::new(offset(delta.x), offset(delta.y))
+        }
+    })
+}
+
+/// Error of [flood_fill], in both cases the region is only partially filled
+#[derive(Debug, PartialEq, Eq)]
+pub enum FloodFillError {
+    /// The region is bigger than `max_pixels`
+    MaxPixelsReached,
+    /// The region is too complex, too many spans had to be remembered at once
+    StackExhausted,
+}
+
+/// Fills the connected region around a pixel of a 1 bit per pixel buffer
+///
+/// The buffer is laid out like in [blit], `(x, y)` is the seed pixel. All the pixels of
+/// the same color as the seed pixel, which are connected to it horizontally or
+/// vertically, are set to `fill`.
+///
+/// The fill works scanline by scanline with a small fixed size stack, so it neither
+/// recurses nor allocates. At most `max_pixels` pixels are filled, to guard against
+/// filling way more than intended. Returns the number of filled pixels.
+pub fn flood_fill(
+    buffer: &mut [u8],
+    width: u32,
+    x: u32,
+    y: u32,
+    fill: Color,
+    max_pixels: usize,
+) -> Result<usize, FloodFillError> {
+    // number of spans to revisit which can be remembered at once
+    const STACK_SIZE: usize = 64;
+
+    let line = line_bytes(width, 1);
+    if line == 0 {
+        return Ok(0);
+    }
+    let height = (buffer.len() / line) as u32;
+    let is_set = |buffer: &[u8], x: u32, y: u32| get_bit(buffer, line, x, y);
+    if x >= width || y >= height {
+        return Ok(0);
+    }
+    let target = is_set(buffer, x, y);
+    let fill = fill == Color::White;
+    if target == fill {
+        return Ok(0);
+    }
+
+    let mut stack = [(0, 0); STACK_SIZE];
+    stack[0] = (x, y);
+    let mut len = 1;
+    let mut filled = 0;
+    let mut exhausted = false;
+    while len > 0 {
+        len -= 1;
+        let (x, y) = stack[len];
+        if is_set(buffer, x, y) != target {
+            continue;
+        }
+
+        // fill the whole span around the pixel
+        let (mut left, mut right) = (x, x);
+        while left > 0 && is_set(buffer, left - 1, y) == target {
+            left -= 1;
+        }
+        while right + 1 < width && is_set(buffer, right + 1, y) == target {
+            right += 1;
+        }
+        let count = (right - left + 1) as usize;
+        if filled + count > max_pixels {
+            return Err(FloodFillError::MaxPixelsReached);
+        }
+        for x in left..=right {
+            set_bit(buffer, line, x, y, fill);
+        }
+        filled += count;
+
+        // remember one pixel of each span to fill in the rows above and below
+        for y in [y.wrapping_sub(1), y + 1] {
+            if y >= height {
+                continue;
+            }
+            let mut in_span = false;
+            for x in left..=right {
+                let is_target = is_set(buffer, x, y) == target;
+                if is_target && !in_span {
+                    if len < STACK_SIZE {
+                        stack[len] = (x, y);
+                        len += 1;
+                    } else {
+                        exhausted = true;
+                    }
+                }
+                in_span = is_target;
+            }
+        }
+    }
+
+    if exhausted {
+        Err(FloodFillError::StackExhausted)
+    } else {
+        Ok(filled)
+    }
+}
+
+// Shared code of the widgets, draws all the pixels inside of the `size` x `size` square
+// at `position` for which `is_set` returns true
+fn draw_widget<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    size: u32,
+    color: D::Color,
+    is_set: impl Fn(i32, i32) -> bool,
+) -> Result<(), D::Error> {
+    let size = size as i32;
+    let area = display.bounding_box();
+    let pixels = (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .filter(|&(x, y)| is_set(x, y))
+        .map(|(x, y)| Pixel(position + Point::new(x, y), color))
+        .filter(|Pixel(point, _)| area.contains(*point));
+    display.draw_iter(pixels)
+}
+
+// Shared code of all the 1 bit per pixel image formats
+//
+// Draws the `source` area of `image`, which is `image_width` pixels wide, with its top
+// left corner at `position`. Every pixel of the image is drawn as a `scale` x `scale`
+// block. `bit` returns the mask selecting the pixel at position `x % 8` inside of its
+// byte.
+#[allow(clippy::too_many_arguments)]
+fn draw_bits<D: DrawTarget>(
+    display: &mut D,
+    position: Point,
+    image: &[u8],
+    image_width: u32,
+    source: Rectangle,
+    scale: u32,
+    color: D::Color,
+    bit: fn(usize) -> u8,
+) -> Result<(), D::Error> {
+    let line_bytes = line_bytes(image_width, 1);
+    if line_bytes == 0 {
+        return Ok(());
+    }
+    let area = display.bounding_box();
+    let (x0, y0) = (source.top_left.x as usize, source.top_left.y as usize);
+    let width = (source.size.width as usize).min((image_width as usize).saturating_sub(x0));
+    let pixels = image
+        .chunks(line_bytes)
+        .skip(y0)
+        .take(source.size.height as usize)
+        .enumerate()
+        .flat_map(move |(y, row)| {
+            (0..width)
+                .filter(move |&x| {
+                    let x = x0 + x;
+                    row.get(x / 8).is_some_and(|byte| byte & bit(x) != 0)
+                })
+                .map(move |x| Point::new(x as i32, y as i32))
+        })
+        .flat_map(move |point| {
+            let scale = scale as i32;
+            (0..scale * scale).map(move |i| point * scale + Point::new(i % scale, i / scale))
+        })
+        .map(|point| Pixel(position + point, color))
+        .filter(|Pixel(point, _)| area.contains(*point));
+    display.draw_iter(pixels)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -393,6 +1408,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn graphics_rotated_size() {
+        let mut display = Display::<200, 100, false, { 200 * 100 / 8 }, Color>::default();
+        assert_eq!(display.size(), Size::new(200, 100));
+
+        display.set_rotation(DisplayRotation::Rotate90);
+        assert_eq!(display.size(), Size::new(100, 200));
+        assert_eq!(display.rows().count(), 100);
+
+        display.set_rotation(DisplayRotation::Rotate180);
+        assert_eq!(display.size(), Size::new(200, 100));
+    }
+
     #[test]
     fn graphics_rotation_90() {
         let mut display = Display::<200, 200, false, { 200 * 200 / 8 }, Color>::default();
@@ -449,4 +1477,603 @@ mod tests {
             assert_eq!(byte, 0);
         }
     }
+
+    #[test]
+    fn graphics_draw_image() {
+        let mut display = Display::<16, 2, false, { 16 * 2 / 8 }, Color>::default();
+        display.clear(Color::Black).ok();
+
+        // 10 pixel wide image: the second byte of each row only holds 2 pixels
+        let image = [0b1111_1111, 0b1111_1111, 0b1000_0000, 0b0100_0000];
+        draw_image(&mut display, Point::new(3, 0), 10, 2, &image, Color::White).ok();
+
+        assert_eq!(display.buffer(), [0x1F, 0xF8, 0x10, 0x08]);
+    }
+
+    #[test]
+    fn graphics_draw_image_clipped() {
+        let mut display = Display::<16, 2, false, { 16 * 2 / 8 }, Color>::default();
+        display.clear(Color::Black).ok();
+
+        let image = [0xFF, 0xFF, 0xFF, 0xFF];
+        let position = Point::new(12, -1);
+        draw_image(&mut display, position, 16, 2, &image, Color::White).ok();
+
+        assert_eq!(display.buffer(), [0x00, 0x0F, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn graphics_blit_aligned() {
+        let src = [0xA5, 0x3C, 0xFF, 0x0F];
+        let mut dst = [0u8; 3 * 3];
+
+        // copy the 16x2 source to (8, 1)
+        blit(&mut dst, 24, 8, 1, &src, 16, 0, 0, 16, 2);
+
+        assert_eq!(dst, [0, 0, 0, 0, 0xA5, 0x3C, 0, 0xFF, 0x0F]);
+    }
+
+    #[test]
+    fn graphics_blit_misaligned() {
+        let src = [0b1011_0011, 0b1100_0000];
+        let mut dst = [0xFF; 2 * 2];
+
+        // copy the 10 pixels of the first row to (3, 1)
+        blit(&mut dst, 16, 3, 1, &src, 16, 0, 0, 10, 1);
+
+        assert_eq!(dst, [0xFF, 0xFF, 0b1111_0110, 0b0111_1111]);
+
+        // copy starting at the 4th source pixel to (0, 0), clipped at the source width
+        let mut dst = [0u8; 2];
+        blit(&mut dst, 16, 0, 0, &src, 12, 3, 0, 16, 1);
+
+        assert_eq!(dst, [0b1001_1110, 0b0000_0000]);
+    }
+
+    #[test]
+    fn graphics_draw_xbm() {
+        let mut display = Display::<16, 2, false, { 16 * 2 / 8 }, Color>::default();
+        display.clear(Color::Black).ok();
+
+        // same image as in graphics_draw_image, but LSB first
+        let xbm = [0b1111_1111, 0b0000_0011, 0b0000_0001, 0b0000_0010];
+        draw_xbm(&mut display, Point::new(3, 0), 10, 2, &xbm, Color::White).ok();
+
+        assert_eq!(display.buffer(), [0x1F, 0xF8, 0x10, 0x08]);
+    }
+
+    #[test]
+    fn graphics_gray4() {
+        let mut display = Display::<8, 2, false, { 8 * 2 * 2 / 8 }, Gray4>::default();
+        display.clear(Gray4::White).ok();
+
+        Pixel(Point::new(0, 0), Gray4::Black)
+            .draw(&mut display)
+            .ok();
+        Pixel(Point::new(1, 0), Gray4::DarkGray)
+            .draw(&mut display)
+            .ok();
+        Pixel(Point::new(6, 1), Gray4::LightGray)
+            .draw(&mut display)
+            .ok();
+
+        assert_eq!(display.buffer(), [0b0001_1111, 0xFF, 0xFF, 0b1111_1011]);
+    }
+
+    #[test]
+    fn graphics_draw_checkbox() {
+        let mut display = Display::<8, 8, false, { 8 * 8 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        draw_checkbox(&mut display, Point::new(0, 0), 8, false, Color::Black).ok();
+        assert_eq!(
+            display.buffer(),
+            [0x00, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x00]
+        );
+
+        draw_checkbox(&mut display, Point::new(0, 0), 8, true, Color::Black).ok();
+        assert_eq!(
+            display.buffer(),
+            [
+                0b0000_0000,
+                0b0111_1110,
+                0b0111_1110,
+                0b0111_1010,
+                0b0101_0110,
+                0b0110_1110,
+                0b0111_1110,
+                0b0000_0000,
+            ]
+        );
+    }
+
+    #[test]
+    fn graphics_draw_radio() {
+        let mut display = Display::<16, 10, false, { 16 * 10 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        draw_radio(&mut display, Point::new(0, 0), 10, true, Color::Black).ok();
+
+        // rows of the 10 pixel wide radio button, black being 0
+        #[rustfmt::skip]
+        let expected = [
+            0b1110_0001, 0b1111_1111,
+            0b1001_1110, 0b0111_1111,
+            0b1011_1111, 0b0111_1111,
+            0b0110_0001, 0b1011_1111,
+            0b0110_0001, 0b1011_1111,
+            0b0110_0001, 0b1011_1111,
+            0b0110_0001, 0b1011_1111,
+            0b1011_1111, 0b0111_1111,
+            0b1001_1110, 0b0111_1111,
+            0b1110_0001, 0b1111_1111,
+        ];
+        assert_eq!(display.buffer(), expected);
+
+        // only the bottom right quarter is left, clipped at the top left corner
+        display.clear(Color::White).ok();
+        draw_radio(&mut display, Point::new(-5, -5), 10, false, Color::Black).ok();
+        assert_eq!(display.buffer()[0], 0b1111_0111);
+    }
+
+    #[test]
+    fn graphics_rows() {
+        let mut display = Display::<12, 3, false, { 2 * 3 }, Color>::default();
+        Pixel(Point::new(0, 0), Color::White)
+            .draw(&mut display)
+            .ok();
+
+        assert_eq!(display.rows().count(), 3);
+        assert!(display.rows().all(|row| row.len() == 2));
+
+        // mirror the rows vertically
+        {
+            let mut rows = display.rows_mut();
+            let (top, bottom) = (rows.next().unwrap(), rows.nth(1).unwrap());
+            top.swap_with_slice(bottom);
+        }
+        assert_eq!(display.buffer(), [0x00, 0x00, 0x00, 0x00, 0x80, 0x00]);
+
+        let mut buffer = [0u8; 2 * 2 * 3];
+        let display = VarDisplay::<TriColor>::new(16, 3, &mut buffer, false).unwrap();
+        assert_eq!(display.rows().count(), 6);
+    }
+
+    #[test]
+    fn graphics_draw_grid() {
+        let mut display = Display::<8, 4, false, { 8 * 4 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        draw_grid(&mut display, 3, 2, Color::Black).ok();
+        assert_eq!(display.buffer(), [0b0110_1101, 0xFF, 0b0110_1101, 0xFF]);
+
+        display.clear(Color::White).ok();
+        draw_grid_lines(&mut display, 3, 2, Color::Black).ok();
+        assert_eq!(display.buffer(), [0x00, 0b0110_1101, 0x00, 0b0110_1101]);
+
+        // no spacing, no grid
+        display.clear(Color::White).ok();
+        draw_grid(&mut display, 0, 2, Color::Black).ok();
+        assert_eq!(display.buffer(), [0xFF; 4]);
+    }
+
+    #[test]
+    fn graphics_draw_icon() {
+        let mut display = Display::<8, 4, false, { 8 * 4 / 8 }, Color>::default();
+        let battery: &[u8] = &[0b1111_0000, 0b1001_1000, 0b1111_0000];
+        let font = IconFont {
+            glyphs: &[(0xE1A4, battery)],
+            width: 5,
+            height: 3,
+        };
+
+        display.clear(Color::White).ok();
+        draw_icon(&mut display, Point::new(1, 1), &font, 0xE1A4, Color::Black).unwrap();
+        assert_eq!(
+            display.buffer(),
+            [0xFF, 0b1000_0111, 0b1011_0011, 0b1000_0111]
+        );
+
+        // unknown icons are a box
+        display.clear(Color::White).ok();
+        draw_icon(&mut display, Point::new(1, 1), &font, 0xE000, Color::Black).unwrap();
+        assert_eq!(
+            display.buffer(),
+            [0xFF, 0b1000_0011, 0b1011_1011, 0b1000_0011]
+        );
+    }
+
+    #[test]
+    fn graphics_draw_ring() {
+        let mut display = Display::<8, 7, false, { 8 * 7 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        draw_ring(&mut display, Point::new(3, 3), 3, 2, Color::Black).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(display.buffer(), [
+            0b1110_1111,
+            0b1000_0011,
+            0b1011_1011,
+            0b0011_1001,
+            0b1011_1011,
+            0b1000_0011,
+            0b1110_1111,
+        ]);
+
+        // clipped, and a filled circle without inner radius
+        display.clear(Color::White).ok();
+        draw_ring(&mut display, Point::new(0, 0), 0, 1, Color::Black).unwrap();
+        assert_eq!(display.buffer()[..2], [0b0011_1111, 0b0111_1111]);
+    }
+
+    #[test]
+    fn graphics_draw_marker() {
+        let mut display = Display::<8, 5, false, { 8 * 5 / 8 }, Color>::default();
+        #[rustfmt::skip]
+        let markers = [
+            (Marker::Cross, [0b0111_0111, 0b1010_1111, 0b1101_1111, 0b1010_1111, 0b0111_0111]),
+            (Marker::Plus, [0b1101_1111, 0b1101_1111, 0b0000_0111, 0b1101_1111, 0b1101_1111]),
+            (Marker::Dot, [0b1000_1111, 0b0000_0111, 0b0000_0111, 0b0000_0111, 0b1000_1111]),
+            (Marker::Square, [0b0000_0111, 0b0111_0111, 0b0111_0111, 0b0111_0111, 0b0000_0111]),
+        ];
+        for (kind, buffer) in markers {
+            display.clear(Color::White).ok();
+            draw_marker(&mut display, Point::new(2, 2), kind, 5, Color::Black).unwrap();
+            assert_eq!(display.buffer(), buffer, "{:?}", kind);
+        }
+
+        // clipped at the border
+        display.clear(Color::White).ok();
+        draw_marker(
+            &mut display,
+            Point::new(0, 0),
+            Marker::Square,
+            3,
+            Color::Black,
+        )
+        .unwrap();
+        assert_eq!(
+            display.buffer(),
+            [0b1011_1111, 0b0011_1111, 0xFF, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn graphics_draw_panel() {
+        use Gray4::{Black as B, LightGray as L, White as W};
+
+        let mut display = Display::<8, 6, false, { 8 * 6 * 2 / 8 }, Gray4>::default();
+        let rows = |display: &Display<8, 6, false, 12, Gray4>| {
+            let mut rows = [[W; 8]; 6];
+            for (y, row) in rows.iter_mut().enumerate() {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    *pixel = display.pixel(Point::new(x as i32, y as i32)).unwrap();
+                }
+            }
+            rows
+        };
+
+        display.clear(W).ok();
+        let area = Rectangle::new(Point::new(1, 1), Size::new(6, 4));
+        draw_panel(&mut display, &area, L, B, 0).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(rows(&display), [
+            [W, W, W, W, W, W, W, W],
+            [W, B, B, B, B, B, B, W],
+            [W, B, L, L, L, L, B, W],
+            [W, B, L, L, L, L, B, W],
+            [W, B, B, B, B, B, B, W],
+            [W, W, W, W, W, W, W, W],
+        ]);
+
+        // rounded and clipped to the display
+        display.clear(W).ok();
+        let area = Rectangle::new(Point::new(0, 0), Size::new(9, 6));
+        draw_panel(&mut display, &area, L, B, 2).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(rows(&display), [
+            [W, B, B, B, B, B, B, B],
+            [B, L, L, L, L, L, L, L],
+            [B, L, L, L, L, L, L, L],
+            [B, L, L, L, L, L, L, L],
+            [B, L, L, L, L, L, L, L],
+            [W, B, B, B, B, B, B, B],
+        ]);
+    }
+
+    #[test]
+    fn graphics_fill_shade() {
+        let mut display = Display::<8, 2, false, { 8 * 2 / 8 }, Color>::default();
+        let area = Rectangle::new(Point::new(-2, 0), Size::new(20, 2));
+
+        // black pixels are 0
+        let shades = [
+            (Shade::Black, [0x00, 0x00]),
+            (Shade::DarkGray, [0x00, 0b0101_0101]),
+            (Shade::Gray, [0b0101_0101, 0b1010_1010]),
+            (Shade::LightGray, [0b0101_0101, 0xFF]),
+            (Shade::White, [0xFF, 0xFF]),
+        ];
+        for (shade, buffer) in shades {
+            fill_shade(&mut display, &area, shade).unwrap();
+            assert_eq!(display.buffer(), buffer, "{:?}", shade);
+        }
+
+        // the pattern is aligned to the display
+        display.clear(Color::White).ok();
+        let area = Rectangle::new(Point::new(3, 0), Size::new(2, 2));
+        fill_shade(&mut display, &area, Shade::Gray).unwrap();
+        assert_eq!(display.buffer(), [0b1111_0111, 0b1110_1111]);
+    }
+
+    #[test]
+    fn graphics_draw_histogram() {
+        let mut display = Display::<8, 5, false, { 8 * 5 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        let area = Rectangle::new(Point::new(0, 0), Size::new(8, 5));
+
+        // 7 pixels for 2 bins, 3 pixels each including the gap
+        draw_histogram(&mut display, &area, &[2, 4], Shade::Black).unwrap();
+        assert_eq!(
+            display.buffer(),
+            [0b0111_0011, 0b0111_0011, 0b0001_0011, 0b0001_0011, 0x00]
+        );
+
+        // only the axes
+        display.clear(Color::White).ok();
+        draw_histogram(&mut display, &area, &[0, 0], Shade::Black).unwrap();
+        draw_histogram(&mut display, &area, &[], Shade::Black).unwrap();
+        assert_eq!(
+            display.buffer(),
+            [0b0111_1111, 0b0111_1111, 0b0111_1111, 0b0111_1111, 0x00]
+        );
+    }
+
+    #[test]
+    fn graphics_dashed_line() {
+        let mut display = Display::<8, 2, false, { 8 * 2 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        draw_dashed_line(
+            &mut display,
+            Point::new(0, 0),
+            Point::new(7, 0),
+            2,
+            1,
+            Color::Black,
+        )
+        .unwrap();
+        // nothing is drawn without dashes, a solid line without gaps
+        draw_dashed_line(
+            &mut display,
+            Point::new(0, 1),
+            Point::new(7, 1),
+            0,
+            1,
+            Color::Black,
+        )
+        .unwrap();
+        assert_eq!(display.buffer(), [0b0010_0100, 0xFF]);
+        draw_dashed_line(
+            &mut display,
+            Point::new(7, 1),
+            Point::new(0, 1),
+            1,
+            0,
+            Color::Black,
+        )
+        .unwrap();
+        assert_eq!(display.buffer(), [0b0010_0100, 0x00]);
+    }
+
+    #[test]
+    fn graphics_dashed_rectangle() {
+        let mut display = Display::<8, 4, false, { 8 * 4 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        let area = Rectangle::new(Point::new(1, 0), Size::new(6, 4));
+        draw_dashed_rectangle(&mut display, &area, 2, 2, Color::Black).unwrap();
+        // the pattern continues over the corners
+        #[rustfmt::skip]
+        assert_eq!(
+            display.buffer(),
+            [
+                0b1001_1001,
+                0b1111_1111,
+                0b1111_1111,
+                0b1001_1001,
+            ]
+        );
+
+        // a single row
+        display.clear(Color::White).ok();
+        let area = Rectangle::new(Point::new(0, 1), Size::new(8, 1));
+        draw_dashed_rectangle(&mut display, &area, 1, 1, Color::Black).unwrap();
+        assert_eq!(display.buffer(), [0xFF, 0b0101_0101, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn graphics_sparkline() {
+        let mut sparkline = Sparkline::<4>::new();
+        assert!(sparkline.is_empty());
+        for value in [7, 1, 2, 3, 4] {
+            sparkline.push(value);
+        }
+        assert_eq!(sparkline.len(), 4);
+        assert!(sparkline.samples().eq([1, 2, 3, 4]));
+
+        let mut display = Display::<8, 4, false, { 8 * 4 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        let area = Rectangle::new(Point::new(0, 0), Size::new(7, 4));
+        sparkline.draw(&mut display, &area, Color::Black).unwrap();
+        assert_eq!(
+            display.buffer(),
+            [0b1111_1001, 0b1110_0111, 0b1001_1111, 0b0111_1111]
+        );
+
+        // equal samples at the bottom
+        display.clear(Color::White).ok();
+        let mut sparkline = Sparkline::<4>::new();
+        sparkline.push(-5);
+        sparkline.push(-5);
+        sparkline.draw(&mut display, &area, Color::Black).unwrap();
+        assert_eq!(display.buffer(), [0xFF, 0xFF, 0xFF, 0b0000_0001]);
+    }
+
+    #[test]
+    fn graphics_supersampled() {
+        let mut display = Display::<4, 1, false, { 4 * 2 / 8 }, Gray4>::default();
+        display.clear(Gray4::White).ok();
+
+        // cover 4, 3, 2 and 1 of the subpixels of the 4 pixels
+        #[rustfmt::skip]
+        let subpixels = [
+            (0, 0), (1, 0), (0, 1), (1, 1),
+            (2, 0), (3, 0), (2, 1),
+            (4, 0), (5, 1),
+            (7, 1),
+        ];
+        let pixels = subpixels
+            .iter()
+            .map(|&(x, y)| Pixel(Point::new(x, y), BinaryColor::On));
+        display.supersampled().draw_iter(pixels).ok();
+
+        assert_eq!(display.pixel(Point::new(0, 0)), Some(Gray4::Black));
+        assert_eq!(display.pixel(Point::new(1, 0)), Some(Gray4::Black));
+        assert_eq!(display.pixel(Point::new(2, 0)), Some(Gray4::DarkGray));
+        assert_eq!(display.pixel(Point::new(3, 0)), Some(Gray4::LightGray));
+        assert_eq!(display.pixel(Point::new(4, 0)), None);
+        assert_eq!(display.supersampled().size(), Size::new(8, 2));
+    }
+
+    #[test]
+    fn graphics_draw_from_atlas() {
+        let mut display = Display::<8, 2, false, { 8 * 2 / 8 }, Color>::default();
+
+        // 3 tiles of 4x2 pixels in a 12 pixel wide atlas
+        let atlas = [0b1000_0110, 0b0000_0000, 0b1001_1111, 0b1111_0000];
+        draw_from_atlas(
+            &mut display,
+            Point::new(2, 0),
+            &atlas,
+            12,
+            4,
+            2,
+            1,
+            Color::White,
+        )
+        .ok();
+        assert_eq!(display.buffer(), [0b0001_1000, 0b0011_1100]);
+
+        // out of the atlas
+        display.clear(Color::Black).ok();
+        draw_from_atlas(
+            &mut display,
+            Point::new(0, 0),
+            &atlas,
+            12,
+            4,
+            2,
+            3,
+            Color::White,
+        )
+        .ok();
+        assert_eq!(display.buffer(), [0x00, 0x00]);
+    }
+
+    #[test]
+    fn graphics_draw_image_scaled() {
+        let mut display = Display::<8, 4, false, { 8 * 4 / 8 }, Color>::default();
+
+        let image = [0b1000_0000, 0b0100_0000];
+        draw_image_scaled(
+            &mut display,
+            Point::new(2, 0),
+            2,
+            2,
+            &image,
+            2,
+            Color::White,
+        )
+        .ok();
+        assert_eq!(
+            display.buffer(),
+            [0b0011_0000, 0b0011_0000, 0b0000_1100, 0b0000_1100]
+        );
+
+        // clipped at the right edge
+        display.clear(Color::Black).ok();
+        draw_image_scaled(
+            &mut display,
+            Point::new(4, 1),
+            2,
+            2,
+            &image,
+            3,
+            Color::White,
+        )
+        .ok();
+        assert_eq!(
+            display.buffer(),
+            [0x00, 0b0000_1110, 0b0000_1110, 0b0000_1110]
+        );
+    }
+
+    #[test]
+    fn graphics_width_not_multiple_of_8() {
+        // example definition taken from epd2in13_v2
+        let mut display = Display::<122, 2, false, { crate::buffer_len(122, 2) }, Color>::default();
+        assert_eq!(display.buffer().len(), 2 * 16);
+        assert!(display.rows().all(|row| row.len() == 16));
+
+        display.clear(Color::White).ok();
+        for row in display.rows() {
+            assert!(row[..15].iter().all(|&byte| byte == 0xFF));
+            // the 6 padding bits stay cleared
+            assert_eq!(row[15], 0b1100_0000);
+        }
+
+        // every row of both buffers is padded
+        let mut buffer = [0u8; 2 * 2 * 16];
+        let display = VarDisplay::<TriColor>::new(122, 2, &mut buffer, false).unwrap();
+        assert_eq!(display.buffer().len(), 2 * 2 * 16);
+    }
+
+    #[test]
+    fn graphics_flood_fill() {
+        // a black 5x4 ring with a white inside and outside, 8 pixels per row
+        #[rustfmt::skip]
+        let mut buffer = [
+            0b1111_1111,
+            0b1000_0011,
+            0b1011_1011,
+            0b1011_1011,
+            0b1000_0011,
+            0b1111_1111,
+        ];
+
+        // fill the inside of the ring
+        assert_eq!(flood_fill(&mut buffer, 8, 3, 2, Color::Black, 100), Ok(6));
+        assert_eq!(buffer[2], 0b1000_0011);
+        assert_eq!(buffer[3], 0b1000_0011);
+
+        // seed pixel has the fill color already
+        assert_eq!(flood_fill(&mut buffer, 8, 1, 1, Color::Black, 100), Ok(0));
+
+        // fill the outside, but allow too few pixels
+        let outside = 6 * 8 - 5 * 4;
+        assert_eq!(
+            flood_fill(&mut buffer.clone(), 8, 0, 0, Color::Black, outside),
+            Ok(outside)
+        );
+        assert_eq!(
+            flood_fill(&mut buffer, 8, 0, 0, Color::Black, outside - 1),
+            Err(FloodFillError::MaxPixelsReached)
+        );
+        let mut buffer = [0b1111_1111; 6];
+        assert_eq!(flood_fill(&mut buffer, 8, 7, 5, Color::Black, 100), Ok(48));
+        assert_eq!(buffer, [0; 6]);
+
+        // a comb with 128 teeth, too many spans to remember at once
+        let mut buffer = [0b1010_1010; 2 * 32];
+        buffer[..32].fill(0xFF);
+        assert_eq!(
+            flood_fill(&mut buffer, 256, 0, 0, Color::Black, 1000),
+            Err(FloodFillError::StackExhausted)
+        );
+    }
 }
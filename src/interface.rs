@@ -5,6 +5,27 @@ use embedded_hal::{
     digital::v2::*,
 };
 
+/// Result of [wait_busy_toggle](DisplayInterface::wait_busy_toggle)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum BusyToggle {
+    /// Busy was asserted and released again
+    Toggled,
+    /// Busy was asserted, but not released before the timeout, contains the time spent
+    /// polling in us
+    StillBusy(u32),
+    /// Busy was never asserted, contains the time spent polling in us
+    NeverBusy(u32),
+}
+
+/// A byte sent over spi, logged with the `testing` feature
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpiEvent {
+    /// Sent as a command (DC low)
+    Command(u8),
+    /// Sent as data (DC high)
+    Data(u8),
+}
+
 /// The Connection Interface of all (?) Waveshare EPD-Devices
 ///
 pub(crate) struct DisplayInterface<SPI, CS, BUSY, DC, RST, DELAY> {
@@ -22,6 +43,17 @@ pub(crate) struct DisplayInterface<SPI, CS, BUSY, DC, RST, DELAY> {
     rst: RST,
     /// number of ms the idle loop should sleep on
     delay_us: u32,
+    /// number of times a failed spi write is retried
+    spi_retries: u8,
+    /// time to wait before retrying a failed spi write, if a delay is at hand
+    spi_retry_delay_us: u32,
+    /// gap after every byte of a frame buffer in us, 0 to send it in one transfer
+    inter_byte_delay_us: u32,
+    /// time spent waiting in wait_until_idle in us, since the last take_waited_us
+    waited_us: u32,
+    /// everything sent over spi, for tests
+    #[cfg(feature = "testing")]
+    log: alloc::vec::Vec<SpiEvent>,
 }
 
 impl<SPI, CS, BUSY, DC, RST, DELAY> DisplayInterface<SPI, CS, BUSY, DC, RST, DELAY>
@@ -47,9 +79,34 @@ where
             dc,
             rst,
             delay_us,
+            spi_retries: 0,
+            spi_retry_delay_us: 0,
+            inter_byte_delay_us: 0,
+            waited_us: 0,
+            #[cfg(feature = "testing")]
+            log: alloc::vec::Vec::new(),
         }
     }
 
+    /// Sets how many times a failed spi write is retried before the error is returned
+    pub(crate) fn set_spi_retries(&mut self, retries: u8) {
+        self.spi_retries = retries;
+    }
+
+    /// Sets the time to wait before retrying a failed spi write
+    ///
+    /// Only writes given a delay wait, those are the data of [stream_paced](DisplayInterface::stream_paced)
+    /// and [cmd_with_data_x_times](DisplayInterface::cmd_with_data_x_times), the others
+    /// are retried right away.
+    pub(crate) fn set_spi_retry_delay_us(&mut self, delay_us: u32) {
+        self.spi_retry_delay_us = delay_us;
+    }
+
+    /// Sets the gap after every byte sent with [stream_paced](DisplayInterface::stream_paced)
+    pub(crate) fn set_inter_byte_delay_us(&mut self, delay_us: u32) {
+        self.inter_byte_delay_us = delay_us;
+    }
+
     /// Basic function for sending [Commands](Command).
     ///
     /// Enables direct interaction with the device with the help of [data()](DisplayInterface::data())
@@ -58,7 +115,9 @@ where
         let _ = self.dc.set_low();
 
         // Transfer the command over spi
-        self.write(spi, &[command.address()])
+        self.write(spi, &[command.address()])?;
+        self.log(SpiEvent::Command, &[command.address()]);
+        Ok(())
     }
 
     /// Basic function for sending an array of u8-values of data over spi
@@ -71,6 +130,7 @@ where
         for val in data.iter().copied() {
             // Transfer data one u8 at a time over spi
             self.write(spi, &[val])?;
+            self.log(SpiEvent::Data, &[val]);
         }
 
         Ok(())
@@ -89,6 +149,150 @@ where
         self.data(spi, data)
     }
 
+    /// Same as [cmd_with_data](DisplayInterface::cmd_with_data) for a buffer of rows of
+    /// `width` pixels
+    ///
+    /// If `width` isn't a multiple of 8, the last byte of every row ends with padding bits
+    /// (see [buffer_len](crate::buffer_len)). They are always sent cleared, whatever the
+    /// buffer contains.
+    pub(crate) fn cmd_with_rows<T: Command>(
+        &mut self,
+        spi: &mut SPI,
+        command: T,
+        data: &[u8],
+        width: u32,
+    ) -> Result<(), SPI::Error> {
+        self.cmd(spi, command)?;
+        let padding = (8 - width % 8) % 8;
+        if padding == 0 {
+            return self.data(spi, data);
+        }
+
+        let mask = 0xFF << padding;
+        for row in data.chunks(crate::buffer_len(width as usize, 1)) {
+            if let Some((last, start)) = row.split_last() {
+                self.data(spi, start)?;
+                self.data(spi, &[last & mask])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as [cmd_with_data](DisplayInterface::cmd_with_data), but CS is held low from
+    /// the command to the last byte of data, so everything is sent in a single transaction
+    pub(crate) fn send_command_with_data<T: Command>(
+        &mut self,
+        spi: &mut SPI,
+        command: T,
+        data: &[u8],
+    ) -> Result<(), SPI::Error> {
+        self.frame_transaction(spi, command, |interface, spi| interface.stream(spi, data))
+    }
+
+    /// Same as [send_command_with_data](DisplayInterface::send_command_with_data), for
+    /// frame buffers
+    ///
+    /// With an inter byte delay set, the delay is waited after every byte of data.
+    pub(crate) fn cmd_with_buffer<T: Command>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        command: T,
+        data: &[u8],
+    ) -> Result<(), SPI::Error> {
+        self.frame_transaction(spi, command, |interface, spi| {
+            interface.stream_paced(spi, delay, data)
+        })
+    }
+
+    /// Sends a command followed by the same byte of data `repetitions` times, all in a
+    /// single transaction
+    pub(crate) fn cmd_with_data_x_times<T: Command>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        command: T,
+        val: u8,
+        repetitions: u32,
+    ) -> Result<(), SPI::Error> {
+        self.frame_transaction(spi, command, |interface, spi| {
+            let block = [val; 64];
+            let mut remaining = repetitions as usize;
+            while remaining > 0 {
+                let len = remaining.min(block.len());
+                interface.stream_with_delay(spi, &block[..len], Some(delay))?;
+                remaining -= len;
+            }
+            Ok(())
+        })
+    }
+
+    /// Sends a command and the data written by `write` in a single transaction
+    ///
+    /// CS is held low from the command until `write` returned, also if it failed, and
+    /// DC is high for everything sent by `write`. Within it, data is sent with
+    /// [stream](DisplayInterface::stream) and
+    /// [stream_paced](DisplayInterface::stream_paced), so a whole frame can be generated
+    /// and sent piece by piece without toggling CS in between.
+    pub(crate) fn frame_transaction<T: Command>(
+        &mut self,
+        spi: &mut SPI,
+        command: T,
+        write: impl FnOnce(&mut Self, &mut SPI) -> Result<(), SPI::Error>,
+    ) -> Result<(), SPI::Error> {
+        let _ = self.cs.set_low();
+
+        // low for the command, high for the data
+        let _ = self.dc.set_low();
+        let result = self
+            .transfer(spi, &[command.address()], None)
+            .and_then(|()| {
+                self.log(SpiEvent::Command, &[command.address()]);
+                let _ = self.dc.set_high();
+                write(self, spi)
+            });
+
+        let _ = self.cs.set_high();
+        result
+    }
+
+    /// Sends data within a [frame_transaction](DisplayInterface::frame_transaction)
+    pub(crate) fn stream(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), SPI::Error> {
+        self.stream_with_delay(spi, data, None)
+    }
+
+    // streams data, waiting with `delay` before retrying a failed write
+    fn stream_with_delay(
+        &mut self,
+        spi: &mut SPI,
+        data: &[u8],
+        delay: Option<&mut DELAY>,
+    ) -> Result<(), SPI::Error> {
+        if !data.is_empty() {
+            self.transfer(spi, data, delay)?;
+            self.log(SpiEvent::Data, data);
+        }
+        Ok(())
+    }
+
+    /// Same as [stream](DisplayInterface::stream), but waits the inter byte delay after
+    /// every byte if one is set
+    pub(crate) fn stream_paced(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        data: &[u8],
+    ) -> Result<(), SPI::Error> {
+        if self.inter_byte_delay_us == 0 {
+            return self.stream_with_delay(spi, data, Some(delay));
+        }
+        for val in data.chunks(1) {
+            self.stream_with_delay(spi, val, Some(delay))?;
+            delay.delay_us(self.inter_byte_delay_us);
+        }
+        Ok(())
+    }
+
     /// Basic function for sending the same byte of data (one u8) multiple times over spi
     ///
     /// Enables direct interaction with the device with the help of [command()](ConnectionInterface::command())
@@ -103,6 +307,7 @@ where
         // Transfer data (u8) over spi
         for _ in 0..repetitions {
             self.write(spi, &[val])?;
+            self.log(SpiEvent::Data, &[val]);
         }
         Ok(())
     }
@@ -112,21 +317,65 @@ where
         // activate spi with cs low
         let _ = self.cs.set_low();
 
-        // transfer spi data
+        self.transfer(spi, data, None)?;
+
+        // deactivate spi with cs high
+        let _ = self.cs.set_high();
+
+        Ok(())
+    }
+
+    // transfers spi data, cs needs to be low already
+    fn transfer(
+        &mut self,
+        spi: &mut SPI,
+        data: &[u8],
+        mut delay: Option<&mut DELAY>,
+    ) -> Result<(), SPI::Error> {
         // Be careful!! Linux has a default limit of 4096 bytes per spi transfer
         // see https://raspberrypi.stackexchange.com/questions/65595/spi-transfer-fails-with-buffer-size-greater-than-4096
         if cfg!(target_os = "linux") {
             for data_chunk in data.chunks(4096) {
-                spi.write(data_chunk)?;
+                self.spi_write(spi, data_chunk, delay.as_deref_mut())?;
             }
         } else {
-            spi.write(data)?;
+            self.spi_write(spi, data, delay)?;
         }
+        Ok(())
+    }
 
-        // deactivate spi with cs high
-        let _ = self.cs.set_high();
+    // records the bytes sent for take_log, only with the testing feature
+    #[cfg_attr(not(feature = "testing"), allow(unused_variables))]
+    fn log(&mut self, event: fn(u8) -> SpiEvent, bytes: &[u8]) {
+        #[cfg(feature = "testing")]
+        self.log.extend(bytes.iter().copied().map(event));
+    }
 
-        Ok(())
+    /// Takes everything sent over spi since the last call
+    #[cfg(feature = "testing")]
+    pub(crate) fn take_log(&mut self) -> alloc::vec::Vec<SpiEvent> {
+        core::mem::take(&mut self.log)
+    }
+
+    // retries failed writes, as configured by set_spi_retries and set_spi_retry_delay_us
+    fn spi_write(
+        &mut self,
+        spi: &mut SPI,
+        data: &[u8],
+        mut delay: Option<&mut DELAY>,
+    ) -> Result<(), SPI::Error> {
+        let mut retries = self.spi_retries;
+        loop {
+            match spi.write(data) {
+                Err(_) if retries > 0 => {
+                    retries -= 1;
+                    if let Some(delay) = delay.as_deref_mut() {
+                        delay.delay_us(self.spi_retry_delay_us);
+                    }
+                }
+                result => return result,
+            }
+        }
     }
 
     /// Waits until device isn't busy anymore (busy == HIGH)
@@ -151,8 +400,39 @@ where
             // -> keep it and leave the decision to the user
             if self.delay_us > 0 {
                 delay.delay_us(self.delay_us);
+                self.waited_us = self.waited_us.saturating_add(self.delay_us);
+            }
+        }
+    }
+
+    /// Waits until the device reports busy, at most `timeout_us`
+    ///
+    /// Returns false if the device wasn't busy within the timeout. Polls in steps of the
+    /// delay given to `new` (at least 1us), the time waited is counted like in
+    /// [wait_until_idle](DisplayInterface::wait_until_idle).
+    pub(crate) fn wait_until_busy(
+        &mut self,
+        delay: &mut DELAY,
+        is_busy_low: bool,
+        timeout_us: u32,
+    ) -> bool {
+        let mut remaining = timeout_us;
+        while !self.is_busy(is_busy_low) {
+            if remaining == 0 {
+                return false;
             }
+            let step = self.delay_us.clamp(1, remaining);
+            delay.delay_us(step);
+            self.waited_us = self.waited_us.saturating_add(step);
+            remaining -= step;
         }
+        true
+    }
+
+    /// Returns the time spent in [wait_until_idle](DisplayInterface::wait_until_idle) since
+    /// the last call, in steps of the delay given to `new`
+    pub(crate) fn take_waited_us(&mut self) -> u32 {
+        core::mem::take(&mut self.waited_us)
     }
 
     /// Same as `wait_until_idle` for device needing a command to probe Busy pin
@@ -194,6 +474,70 @@ where
             || (!is_busy_low && self.busy.is_high().unwrap_or(false))
     }
 
+    /// Same as [is_busy](DisplayInterface::is_busy), but errors while reading the
+    /// busy pin are returned instead of being treated as "not busy"
+    pub(crate) fn try_is_busy(&self, is_busy_low: bool) -> Result<bool, BUSY::Error> {
+        if is_busy_low {
+            self.busy.is_low()
+        } else {
+            self.busy.is_high()
+        }
+    }
+
+    /// Sends a stream recorded with [recording](crate::recording)
+    ///
+    /// Replaying stops at the first malformed or truncated operation, the number of bytes
+    /// of `stream` that were sent is returned.
+    pub(crate) fn replay(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        is_busy_low: bool,
+        stream: &[u8],
+    ) -> Result<usize, SPI::Error> {
+        use crate::recording::*;
+
+        let mut rest = stream;
+        while let Some((&op, tail)) = rest.split_first() {
+            rest = match (op, tail) {
+                (OP_COMMAND, [command, tail @ ..]) => {
+                    let _ = self.dc.set_low();
+                    self.write(spi, &[*command])?;
+                    self.log(SpiEvent::Command, &[*command]);
+                    tail
+                }
+                (OP_DATA, [lo, hi, tail @ ..]) => {
+                    let length = u16::from_le_bytes([*lo, *hi]) as usize;
+                    if tail.len() < length {
+                        break;
+                    }
+                    let _ = self.dc.set_high();
+                    self.write(spi, &tail[..length])?;
+                    self.log(SpiEvent::Data, &tail[..length]);
+                    &tail[length..]
+                }
+                (OP_WAIT_IDLE, tail) => {
+                    self.wait_until_idle(delay, is_busy_low);
+                    tail
+                }
+                (OP_DELAY, [a, b, c, d, tail @ ..]) => {
+                    delay.delay_us(u32::from_le_bytes([*a, *b, *c, *d]));
+                    tail
+                }
+                (OP_RST_LOW, tail) => {
+                    let _ = self.rst.set_low();
+                    tail
+                }
+                (OP_RST_HIGH, tail) => {
+                    let _ = self.rst.set_high();
+                    tail
+                }
+                _ => break,
+            };
+        }
+        Ok(stream.len() - rest.len())
+    }
+
     /// Resets the device.
     ///
     /// Often used to awake the module from deep sleep. See [Epd4in2::sleep()](Epd4in2::sleep())
@@ -212,4 +556,67 @@ where
         // 10ms works fine with just for the 7in5_v2 but this needs to be validated for other devices
         delay.delay_us(200_000);
     }
+
+    /// Resets the device like [reset](DisplayInterface::reset), but polls the busy pin
+    /// instead of blindly waiting 200ms afterwards.
+    ///
+    /// Some panel revisions assert busy while they get ready after a reset. As soon as
+    /// busy was seen asserted and released again this returns `true`.
+    ///
+    /// If busy didn't toggle within `timeout_us`, this falls back to the fixed delay
+    /// of [reset](DisplayInterface::reset) (the time already spent polling is deducted)
+    /// and returns `false`.
+    pub(crate) fn reset_and_wait(
+        &mut self,
+        delay: &mut DELAY,
+        initial_delay: u32,
+        duration: u32,
+        is_busy_low: bool,
+        timeout_us: u32,
+    ) -> bool {
+        let _ = self.rst.set_high();
+        delay.delay_us(initial_delay);
+
+        let _ = self.rst.set_low();
+        delay.delay_us(duration);
+        let _ = self.rst.set_high();
+
+        match self.wait_busy_toggle(delay, is_busy_low, timeout_us) {
+            BusyToggle::Toggled => true,
+            BusyToggle::StillBusy(elapsed) | BusyToggle::NeverBusy(elapsed) => {
+                delay.delay_us(200_000u32.saturating_sub(elapsed));
+                false
+            }
+        }
+    }
+
+    /// Polls the busy pin until it was seen asserted and released again, but at most
+    /// for `timeout_us`
+    pub(crate) fn wait_busy_toggle(
+        &mut self,
+        delay: &mut DELAY,
+        is_busy_low: bool,
+        timeout_us: u32,
+    ) -> BusyToggle {
+        // polling interval, independent from `delay_us` as that one might be 0
+        const POLL_US: u32 = 1_000;
+
+        let mut elapsed = 0;
+        let mut was_busy = false;
+        while elapsed < timeout_us {
+            if self.is_busy(is_busy_low) {
+                was_busy = true;
+            } else if was_busy {
+                return BusyToggle::Toggled;
+            }
+            delay.delay_us(POLL_US);
+            elapsed = elapsed.saturating_add(POLL_US);
+        }
+
+        if was_busy {
+            BusyToggle::StillBusy(elapsed)
+        } else {
+            BusyToggle::NeverBusy(elapsed)
+        }
+    }
 }
@@ -53,6 +53,8 @@
 //!
 //! - Buffersize: Wherever a buffer is used it always needs to be of the size: `width / 8 * length`,
 //!   where width and length being either the full e-ink size or the partial update window size
+//! - If the width is not a multiple of 8 it is rounded up, see [buffer_len]. The padding bits at the end
+//!   of each row are never drawn to by the [graphics] and the drivers send them cleared
 //!
 //! ### SPI
 //!
@@ -60,19 +62,38 @@
 //!
 //! Maximum speed tested by myself was 8Mhz but more should be possible (Ben Krasnow used 18Mhz with his implemenation)
 //!
+//! Every display module exports its `SPI_MODE` and a `SPI_MAX_SPEED_HZ`, the speed it was tested with,
+//! e.g. [epd4in2::SPI_MAX_SPEED_HZ], or a conservative default of 4Mhz for displays not tested yet.
+//!
 #![no_std]
 #![deny(missing_docs)]
 
 #[cfg(feature = "graphics")]
 pub mod graphics;
 
+#[cfg(feature = "graphics")]
+pub mod status_bar;
+
 mod traits;
 
 pub mod color;
 
+pub mod pixelbuf;
+
 /// Interface for the physical connection between display and the controlling device
 mod interface;
 
+pub mod recording;
+
+#[cfg(feature = "three_wire")]
+pub mod three_wire;
+
+#[cfg(feature = "testing")]
+extern crate alloc;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
 pub mod epd1in54;
 pub mod epd1in54_v2;
 pub mod epd1in54b;
@@ -97,9 +118,9 @@ pub(crate) mod type_a;
 
 /// Includes everything important besides the chosen Display
 pub mod prelude {
-    pub use crate::color::{Color, OctColor, TriColor};
+    pub use crate::color::{Color, Gray4, OctColor, TriColor};
     pub use crate::traits::{
-        QuickRefresh, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+        QuickRefresh, RefreshLut, SupportsPartial, WaveshareDisplay, WaveshareThreeColorDisplay,
     };
 
     pub use crate::SPI_MODE;
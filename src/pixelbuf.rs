@@ -0,0 +1,157 @@
+//! Helpers for 1 bit per pixel buffers
+//!
+//! The buffers are laid out like the frame buffers of the displays: row by row, every row
+//! taking `stride` bytes, the pixels packed MSB first, the most significant bit being the
+//! leftmost pixel. See [buffer_len](crate::buffer_len) for the stride of a given width.
+//!
+//! Pixels outside of the buffer are read as cleared and writes to them are ignored.
+
+/// Returns whether the pixel at (`x`, `y`) is set
+pub fn get_bit(buffer: &[u8], stride: usize, x: u32, y: u32) -> bool {
+    let (index, mask) = position(stride, x, y);
+    x < stride as u32 * 8 && buffer.get(index).is_some_and(|byte| byte & mask != 0)
+}
+
+/// Sets (`on`) or clears the pixel at (`x`, `y`)
+pub fn set_bit(buffer: &mut [u8], stride: usize, x: u32, y: u32, on: bool) {
+    let (index, mask) = position(stride, x, y);
+    if x >= stride as u32 * 8 {
+        return;
+    }
+    if let Some(byte) = buffer.get_mut(index) {
+        if on {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+    }
+}
+
+/// Shifts the pixels of row `y` by `shift` pixels, to the right if positive
+///
+/// The pixels shifted out of the row are lost, the ones shifted in are set if `fill` is
+/// set. The row is shifted as a whole, including its padding bits.
+pub fn shift_row(buffer: &mut [u8], stride: usize, y: u32, shift: i32, fill: bool) {
+    let start = y as usize * stride;
+    let row = match buffer.get_mut(start..start + stride) {
+        Some(row) => row,
+        None => return,
+    };
+    let fill = if fill { 0xFF } else { 0x00 };
+    let bytes = (shift.unsigned_abs() / 8) as usize;
+    let bits = shift.unsigned_abs() % 8;
+    // byte at `index` of the row before the shift, `fill` outside of it
+    let byte = |row: &[u8], index: Option<usize>| {
+        index
+            .and_then(|index| row.get(index))
+            .copied()
+            .unwrap_or(fill)
+    };
+
+    if shift > 0 {
+        // right to left, reading only bytes which aren't written yet
+        for i in (0..row.len()).rev() {
+            let high = byte(row, i.checked_sub(bytes));
+            let low = byte(row, i.checked_sub(bytes + 1));
+            row[i] = if bits == 0 {
+                high
+            } else {
+                high >> bits | low << (8 - bits)
+            };
+        }
+    } else if shift < 0 {
+        for i in 0..row.len() {
+            let low = byte(row, Some(i + bytes));
+            let high = byte(row, Some(i + bytes + 1));
+            row[i] = if bits == 0 {
+                low
+            } else {
+                low << bits | high >> (8 - bits)
+            };
+        }
+    }
+}
+
+// index of the byte and mask of the bit of a pixel
+fn position(stride: usize, x: u32, y: u32) -> (usize, u8) {
+    (y as usize * stride + x as usize / 8, 0x80 >> (x % 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_set_bit() {
+        let mut buffer = [0u8; 2 * 3];
+
+        set_bit(&mut buffer, 2, 0, 0, true);
+        set_bit(&mut buffer, 2, 9, 1, true);
+        set_bit(&mut buffer, 2, 15, 2, true);
+        assert_eq!(buffer, [0x80, 0x00, 0x00, 0x40, 0x00, 0x01]);
+        assert!(get_bit(&buffer, 2, 0, 0));
+        assert!(get_bit(&buffer, 2, 9, 1));
+        assert!(get_bit(&buffer, 2, 15, 2));
+        assert!(!get_bit(&buffer, 2, 1, 0));
+
+        set_bit(&mut buffer, 2, 9, 1, false);
+        assert_eq!(buffer[3], 0x00);
+    }
+
+    #[test]
+    fn bit_outside_of_buffer() {
+        let mut buffer = [0u8; 2 * 3];
+
+        // neither wrapping into the next row nor past the end
+        set_bit(&mut buffer, 2, 16, 0, true);
+        set_bit(&mut buffer, 2, 0, 3, true);
+        assert_eq!(buffer, [0x00; 6]);
+
+        let buffer = [0xFF; 2 * 3];
+        assert!(!get_bit(&buffer, 2, 16, 0));
+        assert!(!get_bit(&buffer, 2, 0, 3));
+    }
+
+    #[test]
+    fn shift_row_right() {
+        let mut buffer = [0b1010_0000, 0b0000_0101, 0xFF, 0xFF];
+
+        shift_row(&mut buffer, 2, 0, 3, false);
+        assert_eq!(buffer, [0b0001_0100, 0b0000_0000, 0xFF, 0xFF]);
+
+        shift_row(&mut buffer, 2, 0, 9, true);
+        assert_eq!(buffer, [0xFF, 0b1000_1010, 0xFF, 0xFF]);
+
+        shift_row(&mut buffer, 2, 1, 8, false);
+        assert_eq!(buffer[2..], [0x00, 0xFF]);
+    }
+
+    #[test]
+    fn shift_row_left() {
+        let mut buffer = [0b1010_0000, 0b0000_0101];
+
+        shift_row(&mut buffer, 2, 0, -3, true);
+        assert_eq!(buffer, [0b0000_0000, 0b0010_1111]);
+
+        shift_row(&mut buffer, 2, 0, -10, false);
+        assert_eq!(buffer, [0b1011_1100, 0x00]);
+    }
+
+    #[test]
+    fn shift_row_out_of_range() {
+        let mut buffer = [0b1010_0000, 0b0000_0101];
+
+        shift_row(&mut buffer, 2, 0, 16, false);
+        assert_eq!(buffer, [0x00, 0x00]);
+
+        shift_row(&mut buffer, 2, 0, -100, true);
+        assert_eq!(buffer, [0xFF, 0xFF]);
+
+        // row outside of the buffer
+        shift_row(&mut buffer, 2, 1, 1, false);
+        assert_eq!(buffer, [0xFF, 0xFF]);
+
+        shift_row(&mut buffer, 2, 0, 0, false);
+        assert_eq!(buffer, [0xFF, 0xFF]);
+    }
+}
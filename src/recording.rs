@@ -0,0 +1,218 @@
+//! Recording of the command stream sent to a display, for a later replay
+//!
+//! The byte stream of e.g. an init + update + display + sleep cycle can be captured once
+//! and later be sent again with the `replay` function of a driver (e.g.
+//! [Epd4in2::replay](crate::epd4in2::Epd4in2::replay)), skipping all the logic of the driver.
+//!
+//! For recording, a driver is created with the recording SPI, pins and delay of this
+//! module, all sharing the same [Recorder]:
+//!
+//! ```rust
+//! # use core::cell::RefCell;
+//! use epd_waveshare::{epd4in2::Epd4in2, prelude::*, recording::*};
+//!
+//! // the stream contains the data sent, here both frame buffers of the EPD are cleared
+//! let mut buffer = [0u8; 32 * 1024];
+//! let recorder = RefCell::new(Recorder::new(&mut buffer));
+//! let mut spi = RecordingSpi(&recorder);
+//! let mut delay = RecordingDelay(&recorder);
+//! let (busy, dc, rst) = (RecordingBusy(&recorder), RecordingDc(&recorder), RecordingRst(&recorder));
+//!
+//! let mut epd = Epd4in2::new(&mut spi, NoPin, busy, dc, rst, &mut delay, None).unwrap();
+//! epd.clear_frame(&mut spi, &mut delay).unwrap();
+//! epd.display_frame(&mut spi, &mut delay).unwrap();
+//! epd.sleep(&mut spi, &mut delay).unwrap();
+//!
+//! let recorder = recorder.into_inner();
+//! let stream = recorder.stream().expect("buffer too small");
+//! ```
+//!
+//! The recorded busy pin never reports the display as busy, instead every check of it is
+//! recorded and replayed as a wait until the display is idle.
+//!
+//! # Stream format
+//!
+//! The stream is a sequence of operations, each starting with its code:
+//!
+//! - `0x01 <command>`: send a command
+//! - `0x02 <length: u16 LE> <data>`: send data
+//! - `0x03`: wait until the display is idle
+//! - `0x04 <duration: u32 LE>`: delay in us
+//! - `0x05` / `0x06`: set the reset pin low / high
+
+use core::{cell::RefCell, convert::Infallible};
+use embedded_hal::{
+    blocking::{delay::DelayUs, spi::Write},
+    digital::v2::{InputPin, OutputPin},
+};
+
+pub(crate) const OP_COMMAND: u8 = 0x01;
+pub(crate) const OP_DATA: u8 = 0x02;
+pub(crate) const OP_WAIT_IDLE: u8 = 0x03;
+pub(crate) const OP_DELAY: u8 = 0x04;
+pub(crate) const OP_RST_LOW: u8 = 0x05;
+pub(crate) const OP_RST_HIGH: u8 = 0x06;
+
+/// Records operations into a buffer, see the [module documentation](self)
+pub struct Recorder<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+    /// Start of the last operation, to merge data and delays into it
+    last: Option<usize>,
+    dc_high: bool,
+    overflow: bool,
+}
+
+impl<'a> Recorder<'a> {
+    /// Creates a recorder writing into `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Recorder {
+            buffer,
+            len: 0,
+            last: None,
+            dc_high: false,
+            overflow: false,
+        }
+    }
+
+    /// Gets the recorded stream, None if the buffer was too small
+    pub fn stream(&self) -> Option<&[u8]> {
+        if self.overflow {
+            None
+        } else {
+            Some(&self.buffer[..self.len])
+        }
+    }
+
+    fn last_op(&self) -> Option<u8> {
+        self.last.map(|last| self.buffer[last])
+    }
+
+    fn push_op(&mut self, op: &[u8]) {
+        if self.len + op.len() > self.buffer.len() {
+            self.overflow = true;
+            return;
+        }
+        self.buffer[self.len..self.len + op.len()].copy_from_slice(op);
+        self.last = Some(self.len);
+        self.len += op.len();
+    }
+
+    fn data(&mut self, byte: u8) {
+        if let Some(last) = self.last.filter(|_| self.last_op() == Some(OP_DATA)) {
+            let length = u16::from_le_bytes([self.buffer[last + 1], self.buffer[last + 2]]);
+            if length < u16::MAX && self.len < self.buffer.len() {
+                let length = (length + 1).to_le_bytes();
+                self.buffer[last + 1..last + 3].copy_from_slice(&length);
+                self.buffer[self.len] = byte;
+                self.len += 1;
+                return;
+            }
+        }
+        self.push_op(&[OP_DATA, 1, 0, byte]);
+    }
+
+    fn delay(&mut self, us: u32) {
+        if let Some(last) = self.last.filter(|_| self.last_op() == Some(OP_DELAY)) {
+            let mut duration = [0; 4];
+            duration.copy_from_slice(&self.buffer[last + 1..last + 5]);
+            let duration = u32::from_le_bytes(duration).saturating_add(us);
+            self.buffer[last + 1..last + 5].copy_from_slice(&duration.to_le_bytes());
+            return;
+        }
+        let us = us.to_le_bytes();
+        self.push_op(&[OP_DELAY, us[0], us[1], us[2], us[3]]);
+    }
+
+    fn wait_idle(&mut self) {
+        if self.last_op() != Some(OP_WAIT_IDLE) {
+            self.push_op(&[OP_WAIT_IDLE]);
+        }
+    }
+}
+
+/// Recording SPI, records commands or data depending on the state of [RecordingDc]
+pub struct RecordingSpi<'r, 'a>(pub &'r RefCell<Recorder<'a>>);
+
+impl Write<u8> for RecordingSpi<'_, '_> {
+    type Error = Infallible;
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let mut recorder = self.0.borrow_mut();
+        for &word in words {
+            if recorder.dc_high {
+                recorder.data(word);
+            } else {
+                recorder.push_op(&[OP_COMMAND, word]);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Recording Data/Command pin
+pub struct RecordingDc<'r, 'a>(pub &'r RefCell<Recorder<'a>>);
+
+impl OutputPin for RecordingDc<'_, '_> {
+    type Error = Infallible;
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().dc_high = false;
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().dc_high = true;
+        Ok(())
+    }
+}
+
+/// Recording reset pin
+pub struct RecordingRst<'r, 'a>(pub &'r RefCell<Recorder<'a>>);
+
+impl OutputPin for RecordingRst<'_, '_> {
+    type Error = Infallible;
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().push_op(&[OP_RST_LOW]);
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().push_op(&[OP_RST_HIGH]);
+        Ok(())
+    }
+}
+
+/// Recording busy pin, never busy but every check is recorded as wait until idle
+pub struct RecordingBusy<'r, 'a>(pub &'r RefCell<Recorder<'a>>);
+
+impl InputPin for RecordingBusy<'_, '_> {
+    type Error = Infallible;
+    // busy is either high or low active, so both report "not busy"
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.0.borrow_mut().wait_idle();
+        Ok(false)
+    }
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.0.borrow_mut().wait_idle();
+        Ok(false)
+    }
+}
+
+/// Recording delay
+pub struct RecordingDelay<'r, 'a>(pub &'r RefCell<Recorder<'a>>);
+
+impl DelayUs<u32> for RecordingDelay<'_, '_> {
+    fn delay_us(&mut self, us: u32) {
+        self.0.borrow_mut().delay(us);
+    }
+}
+
+/// Pin doing nothing, e.g. for CS while recording
+pub struct NoPin;
+
+impl OutputPin for NoPin {
+    type Error = Infallible;
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
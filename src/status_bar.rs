@@ -0,0 +1,211 @@
+//! A status bar with an icon on the left, a centered title and a text on the right
+//!
+//! This covers the common top bar of dashboards and weather stations, e.g. a wifi icon,
+//! the name of the current page and the time. The texts are drawn with an
+//! [IconFont] containing a glyph for every character, looked up by their code point, so
+//! every character has the same width and measuring a text is a multiplication.
+//!
+//! ```rust
+//! use epd_waveshare::{color::Color, graphics::IconFont, status_bar::StatusBar};
+//! # use epd_waveshare::graphics::Display;
+//! # let mut display = Display::<64, 8, false, { 64 * 8 / 8 }, Color>::default();
+//! # let (glyphs, icons) = ([(u16::from(b'A'), &[0u8; 8][..])], [(1, &[0u8; 8][..])]);
+//!
+//! let font = IconFont { glyphs: &glyphs, width: 6, height: 8 };
+//! let icons = IconFont { glyphs: &icons, width: 8, height: 8 };
+//!
+//! let mut bar = StatusBar::new(font);
+//! bar.set_left_icon(icons, 1);
+//! bar.set_title("Weather");
+//! bar.set_right_text("12:00");
+//! bar.render(&mut display, 0, 8, Color::Black)?;
+//! # Ok::<(), core::convert::Infallible>(())
+//! ```
+
+use crate::graphics::{draw_icon, IconFont};
+use embedded_graphics_core::prelude::*;
+
+/// Pixels between the slots
+const SLOT_GAP: i32 = 2;
+
+/// Code of the ellipsis (…) ending a clipped title
+const ELLIPSIS: u16 = 0x2026;
+
+/// A status bar, see the [module documentation](self)
+#[derive(Clone, Copy, Debug)]
+pub struct StatusBar<'a> {
+    font: IconFont<'a>,
+    left_icon: Option<(IconFont<'a>, u16)>,
+    title: &'a str,
+    right_text: &'a str,
+}
+
+impl<'a> StatusBar<'a> {
+    /// Creates an empty status bar drawing its texts with `font`
+    pub fn new(font: IconFont<'a>) -> Self {
+        StatusBar {
+            font,
+            left_icon: None,
+            title: "",
+            right_text: "",
+        }
+    }
+
+    /// Sets the icon with `code` of `icons` as the left slot
+    pub fn set_left_icon(&mut self, icons: IconFont<'a>, code: u16) {
+        self.left_icon = Some((icons, code));
+    }
+
+    /// Leaves the left slot empty
+    pub fn clear_left_icon(&mut self) {
+        self.left_icon = None;
+    }
+
+    /// Sets the title of the center slot, an empty title leaves it empty
+    pub fn set_title(&mut self, title: &'a str) {
+        self.title = title;
+    }
+
+    /// Sets the text of the right slot, an empty text leaves it empty
+    pub fn set_right_text(&mut self, text: &'a str) {
+        self.right_text = text;
+    }
+
+    /// Draws the status bar over the whole width of `display`, from row `y` on
+    ///
+    /// The icon is aligned to the left edge and the right text to the right edge, both
+    /// vertically centered in the `height` rows. The title is centered on the bar, but
+    /// moved aside to not overlap the other slots. If it doesn't fit in between, it is
+    /// cut and ends with an ellipsis (…), so the font needs a glyph for it.
+    ///
+    /// Only the set pixels of the glyphs are drawn, clear the bar first when redrawing it.
+    pub fn render<D: DrawTarget>(
+        &self,
+        display: &mut D,
+        y: i32,
+        height: u32,
+        color: D::Color,
+    ) -> Result<(), D::Error> {
+        let bounds = display.bounding_box();
+        let (left, right) = (
+            bounds.top_left.x,
+            bounds.top_left.x + bounds.size.width as i32,
+        );
+        let centered = |glyph_height: u32| y + height.saturating_sub(glyph_height) as i32 / 2;
+
+        let mut title_start = left;
+        if let Some((icons, code)) = &self.left_icon {
+            let position = Point::new(left, centered(icons.height));
+            draw_icon(display, position, icons, *code, color)?;
+            title_start += icons.width as i32 + SLOT_GAP;
+        }
+
+        let char_width = self.font.width as i32;
+        let text_y = centered(self.font.height);
+        let mut title_end = right;
+        if !self.right_text.is_empty() {
+            let x = right - self.right_text.chars().count() as i32 * char_width;
+            self.draw_text(
+                display,
+                Point::new(x, text_y),
+                self.right_text.chars(),
+                color,
+            )?;
+            title_end = x - SLOT_GAP;
+        }
+
+        let length = self.title.chars().count() as i32;
+        let available = (title_end - title_start).max(0);
+        if length * char_width <= available {
+            let x = left + (right - left - length * char_width) / 2;
+            let x = x.min(title_end - length * char_width).max(title_start);
+            self.draw_text(display, Point::new(x, text_y), self.title.chars(), color)
+        } else {
+            let fitting = match char_width {
+                0 => 0,
+                _ => available / char_width,
+            };
+            let cut = self.title.chars().take((fitting - 1).max(0) as usize);
+            let text = cut
+                .chain(char::from_u32(ELLIPSIS as u32))
+                .take(fitting as usize);
+            self.draw_text(display, Point::new(title_start, text_y), text, color)
+        }
+    }
+
+    fn draw_text<D: DrawTarget>(
+        &self,
+        display: &mut D,
+        position: Point,
+        text: impl Iterator<Item = char>,
+        color: D::Color,
+    ) -> Result<(), D::Error> {
+        let mut position = position;
+        for c in text {
+            // characters beyond the font codes are drawn as missing glyphs
+            let code = u16::try_from(u32::from(c)).unwrap_or(u16::MAX);
+            draw_icon(display, position, &self.font, code, color)?;
+            position.x += self.font.width as i32;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::graphics::Display;
+
+    const GLYPHS: [(u16, &[u8]); 3] = [
+        (b'A' as u16, &[0xC0, 0xC0]),
+        (b'B' as u16, &[0x80, 0x80]),
+        (ELLIPSIS, &[0x00, 0xC0]),
+    ];
+    const FONT: IconFont<'static> = IconFont {
+        glyphs: &GLYPHS,
+        width: 2,
+        height: 2,
+    };
+
+    #[test]
+    fn status_bar_slots() {
+        let mut display = Display::<16, 4, false, { 16 * 4 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        let mut bar = StatusBar::new(FONT);
+        bar.set_left_icon(FONT, b'A' as u16);
+        bar.set_title("AAAA");
+        bar.set_right_text("BB");
+        bar.render(&mut display, 1, 2, Color::Black).unwrap();
+        // the title is cut to "AA…" between the icon and the right text
+        #[rustfmt::skip]
+        assert_eq!(
+            display.buffer(),
+            [
+                0xFF, 0xFF,
+                0b0011_0000, 0b1111_0101,
+                0b0011_0000, 0b0011_0101,
+                0xFF, 0xFF,
+            ]
+        );
+    }
+
+    #[test]
+    fn status_bar_centered_title() {
+        let mut display = Display::<16, 4, false, { 16 * 4 / 8 }, Color>::default();
+        display.clear(Color::White).ok();
+        let mut bar = StatusBar::new(FONT);
+        bar.set_title("A");
+        bar.render(&mut display, 0, 4, Color::Black).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(
+            display.buffer(),
+            [
+                0xFF, 0xFF,
+                0b1111_1110, 0b0111_1111,
+                0b1111_1110, 0b0111_1111,
+                0xFF, 0xFF,
+            ]
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Logging of everything sent to a display, for tests without hardware
+//!
+//! Only available with the `testing` feature, which needs `alloc`. All bytes sent by a
+//! driver are logged as [SpiEvent]s and can be taken with its `take_log` function, e.g.
+//! [Epd4in2::take_log](crate::epd4in2::Epd4in2::take_log), to check the exact order of
+//! commands and data.
+//!
+//! Unlike [recording](crate::recording) this doesn't record waits, delays or resets and
+//! isn't meant to be used on the device.
+
+pub use crate::interface::SpiEvent;
@@ -0,0 +1,124 @@
+//! Adapter for displays wired for 3-wire SPI, without a DC pin
+//!
+//! In 3-wire mode the display expects 9 bit words, the first bit tells whether the
+//! remaining 8 bits are data (1) or a command (0). [ThreeWireSpi] and [ThreeWireDc] take
+//! the place of the SPI and the DC pin of a driver and share a [ThreeWire], which
+//! combines every byte with the last state of the DC pin into such a word.
+//!
+//! The SPI needs to be configured for 9 bit words, it gets them in the lower 9 bits of
+//! an `u16`.
+//!
+//! ```rust, no_run
+//! # use embedded_hal_mock::*;
+//! # fn main() -> Result<(), MockError> {
+//! # use core::cell::RefCell;
+//! use epd_waveshare::{epd4in2::Epd4in2, prelude::*, three_wire::*};
+//! # struct Spi9;
+//! # impl embedded_hal::blocking::spi::Write<u16> for Spi9 {
+//! #     type Error = MockError;
+//! #     fn write(&mut self, _: &[u16]) -> Result<(), MockError> { Ok(()) }
+//! # }
+//! # let spi9 = Spi9;
+//! # let (cs, busy, rst) = (pin::Mock::new(&[]), pin::Mock::new(&[]), pin::Mock::new(&[]));
+//! # let mut delay = delay::MockNoop::new();
+//!
+//! // spi9 is configured for 9 bit words
+//! let three_wire = RefCell::new(ThreeWire::new(spi9));
+//! let mut spi = ThreeWireSpi(&three_wire);
+//! let dc = ThreeWireDc(&three_wire);
+//!
+//! let mut epd = Epd4in2::new(&mut spi, cs, busy, dc, rst, &mut delay, None)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use core::{cell::RefCell, convert::Infallible};
+use embedded_hal::{blocking::spi::Write, digital::v2::OutputPin};
+
+/// SPI with 9 bit words and the state of the virtual DC pin, see the
+/// [module documentation](self)
+pub struct ThreeWire<SPI> {
+    spi: SPI,
+    data: bool,
+}
+
+impl<SPI> ThreeWire<SPI> {
+    /// Wraps an SPI configured for 9 bit words
+    pub fn new(spi: SPI) -> Self {
+        ThreeWire { spi, data: false }
+    }
+
+    /// Returns the wrapped SPI
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+/// SPI sending bytes as 9 bit words, with the DC bit set by [ThreeWireDc]
+pub struct ThreeWireSpi<'r, SPI>(pub &'r RefCell<ThreeWire<SPI>>);
+
+impl<SPI: Write<u16>> Write<u8> for ThreeWireSpi<'_, SPI> {
+    type Error = SPI::Error;
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let mut three_wire = self.0.borrow_mut();
+        let dc = if three_wire.data { 0x100 } else { 0 };
+        // convert in chunks, to avoid a write for every single byte
+        let mut buffer = [0u16; 32];
+        for chunk in words.chunks(buffer.len()) {
+            for (word, &byte) in buffer.iter_mut().zip(chunk) {
+                *word = dc | byte as u16;
+            }
+            three_wire.spi.write(&buffer[..chunk.len()])?;
+        }
+        Ok(())
+    }
+}
+
+/// Virtual DC pin, sets the DC bit of the following words of [ThreeWireSpi]
+pub struct ThreeWireDc<'r, SPI>(pub &'r RefCell<ThreeWire<SPI>>);
+
+impl<SPI> OutputPin for ThreeWireDc<'_, SPI> {
+    type Error = Infallible;
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().data = false;
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut().data = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::vec::Vec;
+
+    struct Spi9(Vec<u16>);
+
+    impl Write<u16> for Spi9 {
+        type Error = Infallible;
+        fn write(&mut self, words: &[u16]) -> Result<(), Self::Error> {
+            self.0.extend_from_slice(words);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn three_wire_words() {
+        let three_wire = RefCell::new(ThreeWire::new(Spi9(Vec::new())));
+        let mut spi = ThreeWireSpi(&three_wire);
+        let mut dc = ThreeWireDc(&three_wire);
+
+        dc.set_low().unwrap();
+        spi.write(&[0x50]).unwrap();
+        dc.set_high().unwrap();
+        spi.write(&[0x97; 40]).unwrap();
+
+        let words = three_wire.into_inner().release().0;
+        assert_eq!(words.len(), 41);
+        assert_eq!(words[0], 0x050);
+        assert!(words[1..].iter().all(|&word| word == 0x197));
+    }
+}
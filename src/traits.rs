@@ -178,12 +178,40 @@ where
     fn background_color(&self) -> &Self::DisplayColor;
 
     /// Get the width of the display
+    ///
+    /// This is the physical width the frame buffers are transferred with, it doesn't
+    /// depend on any rotation. The rotated (logical) size to lay out drawings with is
+    /// the `size()` of the [Display](crate::graphics::Display) drawn to.
     fn width(&self) -> u32;
 
     /// Get the height of the display
+    ///
+    /// Like [width](WaveshareDisplay::width) this is the physical, unrotated height.
     fn height(&self) -> u32;
 
+    /// Get the width and height of the display
+    ///
+    /// Same as [width](WaveshareDisplay::width) and [height](WaveshareDisplay::height).
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width(), self.height())
+    }
+
+    /// Get the size of the display for embedded-graphics
+    ///
+    /// Same as [dimensions](WaveshareDisplay::dimensions), so it is the physical,
+    /// unrotated size as well.
+    #[cfg(feature = "graphics")]
+    fn size(&self) -> embedded_graphics_core::geometry::Size {
+        embedded_graphics_core::geometry::Size::new(self.width(), self.height())
+    }
+
     /// Transmit a full frame to the SRAM of the EPD
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` doesn't have the size of a full frame, which is the size of the buffer
+    /// of the `Display` of the driver (of its black/white buffer for tri-color displays
+    /// with a separate chromatic frame).
     fn update_frame(
         &mut self,
         spi: &mut SPI,
@@ -196,6 +224,10 @@ where
     /// (x,y) is the top left corner
     ///
     /// BUFFER needs to be of size: width / 8 * height !
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` doesn't have this size.
     #[allow(clippy::too_many_arguments)]
     fn update_partial_frame(
         &mut self,
@@ -214,6 +246,11 @@ where
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error>;
 
     /// Provide a combined update&display and save some time (skipping a busy check in between)
+    ///
+    /// # Panics
+    ///
+    /// Like [update_frame](WaveshareDisplay::update_frame), if `buffer` doesn't have the
+    /// size of a full frame.
     fn update_and_display_frame(
         &mut self,
         spi: &mut SPI,
@@ -366,3 +403,38 @@ where
         height: u32,
     ) -> Result<(), SPI::Error>;
 }
+
+/// Marker for displays whose [update_partial_frame](WaveshareDisplay::update_partial_frame)
+/// actually transmits the partial frame
+///
+/// Some displays don't support partial updates, calling it on them panics or does nothing.
+/// Generic code relying on partial updates can require this trait, so using it with such a
+/// display won't compile:
+///
+///```rust
+///# use embedded_hal::{blocking::{delay::DelayUs, spi::Write}, digital::v2::{InputPin, OutputPin}};
+///use epd_waveshare::prelude::*;
+///
+///fn update_clock<SPI, CS, BUSY, DC, RST, DELAY, EPD>(
+///    epd: &mut EPD,
+///    spi: &mut SPI,
+///    delay: &mut DELAY,
+///    clock: &[u8],
+///) -> Result<(), SPI::Error>
+///where
+///    SPI: Write<u8>,
+///    CS: OutputPin,
+///    BUSY: InputPin,
+///    DC: OutputPin,
+///    RST: OutputPin,
+///    DELAY: DelayUs<u32>,
+///    EPD: WaveshareDisplay<SPI, CS, BUSY, DC, RST, DELAY> + SupportsPartial,
+///{
+///    epd.update_partial_frame(spi, delay, clock, 0, 0, 64, 16)?;
+///    epd.display_frame(spi, delay)
+///}
+///```
+///
+/// The other capabilities have their own traits: three color displays implement
+/// [WaveshareThreeColorDisplay], displays with a quick refresh [QuickRefresh].
+pub trait SupportsPartial {}